@@ -0,0 +1,81 @@
+use bm::{OwnedList, Raw, Owned, Index};
+use bm::utils::vector_tree;
+use criterion::{criterion_group, criterion_main, Criterion};
+use generic_array::GenericArray;
+use sha2::Sha256;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Ord, PartialOrd, Hash)]
+struct BenchValue([u8; 32]);
+
+impl AsRef<[u8]> for BenchValue {
+	fn as_ref(&self) -> &[u8] {
+		self.0.as_ref()
+	}
+}
+
+impl From<usize> for BenchValue {
+	fn from(value: usize) -> Self {
+		let mut bytes = [0u8; 32];
+		bytes[0..8].copy_from_slice(&(value as u64).to_le_bytes()[..]);
+		BenchValue(bytes)
+	}
+}
+
+impl From<BenchValue> for usize {
+	fn from(value: BenchValue) -> usize {
+		let mut raw = [0u8; 8];
+		raw.copy_from_slice(&value.0[0..8]);
+		u64::from_le_bytes(raw) as usize
+	}
+}
+
+impl From<GenericArray<u8, typenum::U32>> for BenchValue {
+	fn from(array: GenericArray<u8, typenum::U32>) -> BenchValue {
+		let mut ret = [0u8; 32];
+		ret.copy_from_slice(&array[0..32]);
+		BenchValue(ret)
+	}
+}
+
+type Construct = bm::InheritedDigestConstruct<Sha256, BenchValue>;
+type InMemory = bm::InMemoryBackend<Construct>;
+
+fn build_vector_tree(c: &mut Criterion) {
+	let values = (0..100_000).map(BenchValue::from).collect::<Vec<_>>();
+
+	c.bench_function("vector_tree 100k", |b| b.iter(|| {
+		let mut db = InMemory::default();
+		vector_tree(&values, &mut db, None).unwrap()
+	}));
+}
+
+fn list_push_pop(c: &mut Criterion) {
+	c.bench_function("list push pop 1k", |b| b.iter(|| {
+		let mut db = InMemory::default();
+		let mut list = OwnedList::<Construct>::create(&mut db, None).unwrap();
+		for i in 0..1000 {
+			list.push(&mut db, i.into()).unwrap();
+		}
+		for _ in 0..1000 {
+			list.pop(&mut db).unwrap();
+		}
+	}));
+}
+
+fn raw_set_deep(c: &mut Criterion) {
+	c.bench_function("raw set 1k at depth 32", |b| b.iter(|| {
+		let mut db = InMemory::default();
+		let mut raw = Raw::<Owned, Construct>::default();
+		for i in 0..1000usize {
+			let index = Index::from_depth((i * 2_654_435_761) % (1 << 32), 32);
+			raw.set(&mut db, index, i.into()).unwrap();
+		}
+	}));
+}
+
+criterion_group! {
+	name = benches;
+	config = Criterion::default().sample_size(10);
+	targets = build_vector_tree, list_push_pop, raw_set_deep
+}
+criterion_main!(benches);
@@ -0,0 +1,221 @@
+use bm_le::{Backend, ReadBackend, WriteBackend, Construct, InMemoryBackend, DigestConstruct,
+			IntoTree, FromTree, Value};
+use bm::{OwnedList, OwnedVector, OwnedPackedList};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use generic_array::GenericArray;
+use sha2::Sha256;
+use typenum::{U8, U32};
+
+type C = DigestConstruct<Sha256>;
+type InMemory = InMemoryBackend<C>;
+type PackedList = OwnedPackedList<C, GenericArray<u8, U8>, U32, U8>;
+
+const SIZES: [usize; 3] = [1_000, 10_000, 100_000];
+const RANDOM_SETS: usize = 1_000;
+
+/// Backend wrapper counting hashes (every inserted node is one hash) and
+/// node reads.
+#[derive(Default)]
+struct MeteredBackend<DB> {
+	db: DB,
+	hashes: usize,
+	reads: usize,
+}
+
+impl<DB: Backend> Backend for MeteredBackend<DB> {
+	type Construct = DB::Construct;
+	type Error = DB::Error;
+}
+
+impl<DB: ReadBackend> ReadBackend for MeteredBackend<DB> {
+	fn get(
+		&mut self,
+		key: &<Self::Construct as Construct>::Value,
+	) -> Result<Option<(<Self::Construct as Construct>::Value, <Self::Construct as Construct>::Value)>, Self::Error> {
+		self.reads += 1;
+		self.db.get(key)
+	}
+}
+
+impl<DB: WriteBackend> WriteBackend for MeteredBackend<DB> {
+	fn rootify(
+		&mut self,
+		key: &<Self::Construct as Construct>::Value,
+	) -> Result<(), Self::Error> {
+		self.db.rootify(key)
+	}
+
+	fn unrootify(
+		&mut self,
+		key: &<Self::Construct as Construct>::Value,
+	) -> Result<(), Self::Error> {
+		self.db.unrootify(key)
+	}
+
+	fn insert(
+		&mut self,
+		key: <Self::Construct as Construct>::Value,
+		value: (<Self::Construct as Construct>::Value, <Self::Construct as Construct>::Value)
+	) -> Result<(), Self::Error> {
+		self.hashes += 1;
+		self.db.insert(key, value)
+	}
+}
+
+type Metered = MeteredBackend<InMemory>;
+
+fn report<F: FnOnce(&mut Metered)>(name: &str, f: F) {
+	let mut db = Metered::default();
+	f(&mut db);
+	eprintln!("{}: {} hashes, {} reads", name, db.hashes, db.reads);
+}
+
+fn random_index(i: usize, len: usize) -> usize {
+	i.wrapping_mul(2_654_435_761) % len
+}
+
+fn filled_list(db: &mut Metered, len: usize) -> OwnedList<C> {
+	let mut list = OwnedList::create(db, None).unwrap();
+	for i in 0..len {
+		list.push(db, Value::from(i)).unwrap();
+	}
+	list
+}
+
+fn filled_vector(db: &mut Metered, len: usize) -> OwnedVector<C> {
+	OwnedVector::create_with(db, len, None, Value::from).unwrap()
+}
+
+fn filled_packed_list(db: &mut Metered, len: usize) -> PackedList {
+	let mut list = PackedList::create(db, None).unwrap();
+	for i in 0..len {
+		list.push(db, GenericArray::clone_from_slice(&(i as u64).to_le_bytes())).unwrap();
+	}
+	list
+}
+
+fn list(c: &mut Criterion) {
+	for &len in &SIZES {
+		report(&format!("list push {}", len), |db| { filled_list(db, len); });
+		c.bench_function(&format!("list push {}", len), |b| b.iter(|| {
+			filled_list(&mut Metered::default(), len)
+		}));
+
+		report(&format!("list pop {}", len), |db| {
+			let mut list = filled_list(db, len);
+			db.hashes = 0;
+			db.reads = 0;
+			while list.pop(db).unwrap().is_some() { }
+		});
+		c.bench_function(&format!("list pop {}", len), |b| b.iter_batched(|| {
+			let mut db = Metered::default();
+			let list = filled_list(&mut db, len);
+			(db, list)
+		}, |(mut db, mut list)| {
+			while list.pop(&mut db).unwrap().is_some() { }
+		}, BatchSize::LargeInput));
+
+		report(&format!("list random set {}", len), |db| {
+			let mut list = filled_list(db, len);
+			db.hashes = 0;
+			db.reads = 0;
+			for i in 0..RANDOM_SETS {
+				list.set(db, random_index(i, len), Value::from(i)).unwrap();
+			}
+		});
+		c.bench_function(&format!("list random set {}", len), |b| b.iter_batched(|| {
+			let mut db = Metered::default();
+			let list = filled_list(&mut db, len);
+			(db, list)
+		}, |(mut db, mut list)| {
+			for i in 0..RANDOM_SETS {
+				list.set(&mut db, random_index(i, len), Value::from(i)).unwrap();
+			}
+		}, BatchSize::LargeInput));
+	}
+}
+
+fn vector(c: &mut Criterion) {
+	for &len in &SIZES {
+		report(&format!("vector push {}", len), |db| {
+			let mut vector = OwnedVector::<C>::create(db, 0, None).unwrap();
+			for i in 0..len {
+				vector.push(db, Value::from(i)).unwrap();
+			}
+		});
+		c.bench_function(&format!("vector push {}", len), |b| b.iter(|| {
+			let mut db = Metered::default();
+			let mut vector = OwnedVector::<C>::create(&mut db, 0, None).unwrap();
+			for i in 0..len {
+				vector.push(&mut db, Value::from(i)).unwrap();
+			}
+		}));
+
+		report(&format!("vector pop {}", len), |db| {
+			let mut vector = filled_vector(db, len);
+			db.hashes = 0;
+			db.reads = 0;
+			while vector.pop(db).unwrap().is_some() { }
+		});
+		c.bench_function(&format!("vector pop {}", len), |b| b.iter_batched(|| {
+			let mut db = Metered::default();
+			let vector = filled_vector(&mut db, len);
+			(db, vector)
+		}, |(mut db, mut vector)| {
+			while vector.pop(&mut db).unwrap().is_some() { }
+		}, BatchSize::LargeInput));
+	}
+}
+
+fn packed_list(c: &mut Criterion) {
+	for &len in &SIZES {
+		report(&format!("packed list push {}", len), |db| { filled_packed_list(db, len); });
+		c.bench_function(&format!("packed list push {}", len), |b| b.iter(|| {
+			filled_packed_list(&mut Metered::default(), len)
+		}));
+
+		report(&format!("packed list pop {}", len), |db| {
+			let mut list = filled_packed_list(db, len);
+			db.hashes = 0;
+			db.reads = 0;
+			while list.pop(db).unwrap().is_some() { }
+		});
+		c.bench_function(&format!("packed list pop {}", len), |b| b.iter_batched(|| {
+			let mut db = Metered::default();
+			let list = filled_packed_list(&mut db, len);
+			(db, list)
+		}, |(mut db, mut list)| {
+			while list.pop(&mut db).unwrap().is_some() { }
+		}, BatchSize::LargeInput));
+	}
+}
+
+fn reconstruct(c: &mut Criterion) {
+	for &len in &SIZES {
+		let values = (0..len).map(|i| (i as u64, i as u64)).collect::<Vec<_>>();
+
+		report(&format!("into_tree {}", len), |db| { values.into_tree(db).unwrap(); });
+		c.bench_function(&format!("into_tree {}", len), |b| b.iter(|| {
+			values.into_tree(&mut Metered::default()).unwrap()
+		}));
+
+		report(&format!("from_tree {}", len), |db| {
+			let root = values.into_tree(db).unwrap();
+			db.hashes = 0;
+			db.reads = 0;
+			Vec::<(u64, u64)>::from_tree(&root, db).unwrap();
+		});
+		let mut db = Metered::default();
+		let root = values.into_tree(&mut db).unwrap();
+		c.bench_function(&format!("from_tree {}", len), |b| b.iter(|| {
+			Vec::<(u64, u64)>::from_tree(&root, &mut db).unwrap()
+		}));
+	}
+}
+
+criterion_group! {
+	name = benches;
+	config = Criterion::default().sample_size(10);
+	targets = list, vector, packed_list, reconstruct
+}
+criterion_main!(benches);
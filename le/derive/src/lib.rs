@@ -3,12 +3,81 @@
 extern crate proc_macro;
 
 use quote::{quote, quote_spanned};
-use syn::{parse_macro_input, Fields, Ident, DeriveInput, Data};
+use syn::{parse_macro_input, Fields, Ident, DeriveInput, Data, Attribute, Meta, NestedMeta, Lit, Type};
 use syn::spanned::Spanned;
 use deriving::{has_attribute, normalized_fields, is_fields_variant_unnamed, normalized_variant_match_cause};
 
 use proc_macro::TokenStream;
 
+fn field_order(attrs: &[Attribute]) -> Option<u64> {
+	for attr in attrs {
+		if let Ok(Meta::List(list)) = attr.parse_meta() {
+			if list.ident != "bm" {
+				continue
+			}
+
+			for nested in &list.nested {
+				if let NestedMeta::Meta(Meta::NameValue(value)) = nested {
+					if value.ident == "order" {
+						match &value.lit {
+							Lit::Int(order) => return Some(order.value()),
+							_ => panic!("bm(order) must be an integer"),
+						}
+					}
+				}
+			}
+		}
+	}
+
+	None
+}
+
+/// Whether the type is a `PhantomData`, which contributes no field.
+fn is_phantom(ty: &Type) -> bool {
+	match ty {
+		Type::Path(path) => path.path.segments.last()
+			.map(|segment| segment.value().ident == "PhantomData")
+			.unwrap_or(false),
+		_ => false,
+	}
+}
+
+/// Merkleization position of each field, in declaration order. Fields
+/// are sorted by `#[bm(order = N)]`, defaulting to their declaration
+/// index, with ties kept in declaration order. The same explicit order on
+/// two fields is an error. `PhantomData` fields are skipped and have no
+/// position.
+fn field_positions(fields: &Fields) -> Vec<Option<usize>> {
+	let fields = normalized_fields(fields);
+	let mut explicit = Vec::new();
+	let mut sorted = fields.iter()
+		.enumerate()
+		.filter(|(_, f)| !is_phantom(&f.1.ty))
+		.map(|(i, f)| match field_order(&f.1.attrs) {
+			Some(order) => {
+				if explicit.contains(&order) {
+					panic!("bm(order = {}) is used by more than one field", order);
+				}
+				explicit.push(order);
+				(order, i)
+			},
+			None => (i as u64, i),
+		})
+		.collect::<Vec<_>>();
+	sorted.sort();
+
+	let mut positions = vec![None; fields.len()];
+	for (position, (_, i)) in sorted.into_iter().enumerate() {
+		positions[i] = Some(position);
+	}
+	positions
+}
+
+/// Number of merkleized fields, excluding `PhantomData` fields.
+fn field_count(fields: &Fields) -> usize {
+	normalized_fields(fields).iter().filter(|f| !is_phantom(&f.1.ty)).count()
+}
+
 #[proc_macro_derive(IntoTree, attributes(bm))]
 pub fn into_tree_derive(input: TokenStream) -> TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
@@ -19,6 +88,7 @@ pub fn into_tree_derive(input: TokenStream) -> TokenStream {
 	let build_fields = |fs, prefix| {
 		let where_fields = normalized_fields(fs)
 			.iter()
+			.filter(|f| !is_phantom(&f.1.ty))
 			.map(|f| {
 				let ty = &f.1.ty;
 
@@ -33,12 +103,15 @@ pub fn into_tree_derive(input: TokenStream) -> TokenStream {
 				}
 			}).collect::<Vec<_>>();
 
-		let fields = normalized_fields(fs)
+		let positions = field_positions(fs);
+		let mut fields = normalized_fields(fs)
 			.iter()
-			.map(|f| {
+			.zip(positions)
+			.filter_map(|(f, position)| position.map(|position| (f, position)))
+			.map(|(f, position)| {
 				let ident = &f.0;
 
-				if has_attribute("bm", &f.1.attrs, "compact") {
+				(position, if has_attribute("bm", &f.1.attrs, "compact") {
 					quote_spanned! { f.1.span() => {
 						vector.push(bm_le::IntoTree::into_tree(&bm_le::CompactRef(#prefix #ident), db)?);
 					} }
@@ -46,8 +119,10 @@ pub fn into_tree_derive(input: TokenStream) -> TokenStream {
 					quote_spanned! { f.1.span() => {
 						vector.push(bm_le::IntoTree::into_tree(#prefix #ident, db)?);
 					} }
-				}
+				})
 			}).collect::<Vec<_>>();
+		fields.sort_by_key(|f| f.0);
+		let fields = fields.into_iter().map(|f| f.1);
 
 		let inner = quote! {
 			let mut vector = Vec::new();
@@ -93,7 +168,76 @@ pub fn into_tree_derive(input: TokenStream) -> TokenStream {
 		Data::Union(_) => panic!("Unsupported"),
 	};
 
+	let depth = match input.data {
+		Data::Struct(ref data) => {
+			let len = field_count(&data.fields) as u64;
+			quote! { bm_le::utils::required_depth(#len) }
+		},
+		Data::Enum(_) => quote! { 1 },
+		Data::Union(_) => panic!("Unsupported"),
+	};
+
+	let accessors = match input.data {
+		Data::Struct(ref data) => match data.fields {
+			Fields::Named(_) => {
+				let fields = normalized_fields(&data.fields);
+				let positions = field_positions(&data.fields);
+				let len = field_count(&data.fields) as u64;
+
+				let methods = fields.iter().zip(positions).filter_map(|(f, i)| i.map(|i| (f, i))).map(|(f, i)| {
+					let ident = f.1.ident.as_ref().expect("fields are named; qed");
+					let ty = &f.1.ty;
+					let gindex_name = Ident::new(&format!("{}_gindex", ident), ident.span());
+					let root_name = Ident::new(&format!("{}_root", ident), ident.span());
+					let gindex_doc = format!("Generalized index of field `{}`.", ident);
+					let root_doc = format!("Subtree root of field `{}`.", ident);
+
+					let (bound, value) = if has_attribute("bm", &f.1.attrs, "compact") {
+						(quote! { for<'a> bm_le::CompactRef<'a, #ty>: bm_le::IntoTree },
+						 quote! { &bm_le::CompactRef(&self.#ident) })
+					} else {
+						(quote! { #ty: bm_le::IntoTree },
+						 quote! { &self.#ident })
+					};
+
+					quote_spanned! { f.1.span() =>
+						#[doc = #gindex_doc]
+						pub fn #gindex_name() -> bm_le::Index {
+							bm_le::Index::from_depth(#i, bm_le::utils::required_depth(#len))
+						}
+
+						#[doc = #root_doc]
+						pub fn #root_name<DB: bm_le::WriteBackend>(
+							&self,
+							db: &mut DB
+						) -> Result<<DB::Construct as bm_le::Construct>::Value, bm_le::Error<DB::Error>> where
+							DB::Construct: bm_le::CompatibleConstruct,
+							#bound
+						{
+							bm_le::IntoTree::into_tree(#value, db)
+						}
+					}
+				});
+
+				quote! {
+					#[allow(dead_code)]
+					impl #impl_generics #name #ty_generics #where_clause {
+						#(#methods)*
+					}
+				}
+			},
+			_ => quote! { },
+		},
+		_ => quote! { },
+	};
+
 	let expanded = quote! {
+		impl #impl_generics bm_le::TreeShape for #name #ty_generics #where_clause {
+			fn depth() -> usize { #depth }
+		}
+
+		#accessors
+
 		impl #impl_generics bm_le::IntoTree for #name #ty_generics where
 			#where_clause
 			#(#where_fields),*
@@ -122,6 +266,7 @@ pub fn from_tree_derive(input: TokenStream) -> TokenStream {
 	let build_fields = |fs| {
 		let where_fields = normalized_fields(fs)
 			.iter()
+			.filter(|f| !is_phantom(&f.1.ty))
 			.map(|f| {
 			let ty = &f.1.ty;
 
@@ -138,39 +283,40 @@ pub fn from_tree_derive(input: TokenStream) -> TokenStream {
 
 		let fields = normalized_fields(fs)
 			.iter()
-			.enumerate()
-			.map(|(i, f)| {
+			.zip(field_positions(fs))
+			.map(|(f, i)| {
 				let name = &f.0;
 				let ty = &f.1.ty;
 
 				(quote_spanned! { f.1.span() => #name },
-				 if has_attribute("bm", &f.1.attrs, "compact") {
-					 quote_spanned! {
-						 f.1.span() =>
-							 <bm_le::Compact<#ty> as bm_le::FromTree>::from_tree(
-								 &vector.get(db, #i)?,
-								 db,
-							 )?.0
-					 }
-				 } else {
-					 quote_spanned! {
-						 f.1.span() =>
-							 bm_le::FromTree::from_tree(
-								 &vector.get(db, #i)?,
-								 db,
-							 )?
-					 }
+				 match i {
+					 None => quote_spanned! { f.1.span() => core::marker::PhantomData },
+					 Some(i) => if has_attribute("bm", &f.1.attrs, "compact") {
+						 quote_spanned! {
+							 f.1.span() =>
+								 <bm_le::Compact<#ty> as bm_le::FromTree>::from_tree(
+									 &vector.get(db, #i)?,
+									 db,
+								 )?.0
+						 }
+					 } else {
+						 quote_spanned! {
+							 f.1.span() =>
+								 bm_le::FromTree::from_tree(
+									 &vector.get(db, #i)?,
+									 db,
+								 )?
+						 }
+					 },
 				 })
 			}).collect::<Vec<_>>();
 
-		(where_fields, fields)
+		(where_fields, fields, field_count(fs))
 	};
 
 	let (where_fields, inner) = match input.data {
 		Data::Struct(ref data) => {
-			let (where_fields, fields) = build_fields(&data.fields);
-
-			let fields_count = fields.iter().count();
+			let (where_fields, fields, fields_count) = build_fields(&data.fields);
 			let fields = fields.into_iter().map(|f| {
 				let name = f.0;
 				let value = f.1;
@@ -203,13 +349,12 @@ pub fn from_tree_derive(input: TokenStream) -> TokenStream {
 				.iter()
 				.enumerate()
 				.map(|(i, variant)| {
-					let (mut variant_where_fields, variant_fields) = build_fields(
+					let (mut variant_where_fields, variant_fields, fields_count) = build_fields(
 						&variant.fields,
 					);
 					let ident = &variant.ident;
 
 					where_fields.append(&mut variant_where_fields);
-					let fields_count = variant_fields.iter().count();
 
 					match variant.fields {
 						Fields::Named(_) => {
@@ -314,7 +459,11 @@ pub fn partialable_derive(input: TokenStream) -> TokenStream {
 
 	let expanded = match input.data {
 		Data::Struct(ref data) => {
-			let fields = normalized_fields(&data.fields);
+			let (fields, positions): (Vec<_>, Vec<_>) = normalized_fields(&data.fields)
+				.into_iter()
+				.zip(field_positions(&data.fields))
+				.filter_map(|(f, i)| i.map(|i| (f, i)))
+				.unzip();
 
 			let struct_inner = fields.clone().into_iter().map(|f| {
 				let name = &f.0;
@@ -327,7 +476,7 @@ pub fn partialable_derive(input: TokenStream) -> TokenStream {
 
 			let total = fields.len();
 
-			let new_inner = fields.clone().into_iter().enumerate().map(|(i, f)| {
+			let new_inner = fields.clone().into_iter().zip(positions.clone()).map(|(f, i)| {
 				let name = &f.0;
 
 				quote! {
@@ -335,6 +484,18 @@ pub fn partialable_derive(input: TokenStream) -> TokenStream {
 				}
 			});
 
+			let path_inner = fields.clone().into_iter().zip(positions).map(|(f, i)| {
+				let name = &f.0;
+				let ty = &f.1.ty;
+				let field = name.to_string();
+
+				quote! {
+					#field => <#ty as bm_le::Partialable>::partial_path(
+						partial_index.vector(#i, #total), &path[1..]
+					),
+				}
+			});
+
 			let flush_inner = fields.clone().into_iter().map(|f| {
 				let name = &f.0;
 
@@ -371,6 +532,20 @@ pub fn partialable_derive(input: TokenStream) -> TokenStream {
 
 				impl bm_le::Partialable for #name {
 					type Value = #partial_name;
+
+					fn partial_path(
+						partial_index: bm_le::PartialIndex,
+						path: &[&str],
+					) -> Option<bm_le::PartialIndex> {
+						if path.is_empty() {
+							return Some(partial_index)
+						}
+
+						match path[0] {
+							#(#path_inner)*
+							_ => None,
+						}
+					}
 				}
 			}
 		},
@@ -1,13 +1,15 @@
-use bm_le::MaxVec;
-use bm_le_derive::{FromTree, IntoTree};
+use bm_le::{MaxVec, IntoTree, FromTree, DigestConstruct};
 use generic_array::{GenericArray, ArrayLength};
+use bm::InMemoryBackend;
+use sha2::Sha256;
+use core::convert::TryFrom;
 
 pub trait Config {
 	type D: ArrayLength<u64>;
 	type E: ArrayLength<u64>;
 }
 
-#[derive(IntoTree, FromTree)]
+#[derive(IntoTree, FromTree, PartialEq, Eq, Debug)]
 pub struct Container<C: Config> {
 	a: u32,
 	b: u64,
@@ -25,3 +27,30 @@ pub enum EnumTest {
 	},
 	E,
 }
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct SmallConfig;
+
+impl Config for SmallConfig {
+	type D = typenum::U3;
+	type E = typenum::U5;
+}
+
+#[test]
+fn test_config_array() {
+	let container = |i: u64| Container::<SmallConfig> {
+		a: i as u32,
+		b: i,
+		c: i as u128,
+		d: GenericArray::from_exact_iter(vec![i, i + 1, i + 2]).unwrap(),
+		e: MaxVec::try_from(vec![i; i as usize]).unwrap(),
+	};
+	let array = GenericArray::<Container<SmallConfig>, typenum::U2>::from_exact_iter(
+		vec![container(1), container(2)]
+	).unwrap();
+
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let root = array.into_tree(&mut db).unwrap();
+	let decoded = GenericArray::<Container<SmallConfig>, typenum::U2>::from_tree(&root, &mut db).unwrap();
+	assert_eq!(decoded, array);
+}
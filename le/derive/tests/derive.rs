@@ -1,8 +1,10 @@
 use sha2::{Digest, Sha256};
 use primitive_types::H256;
-use bm::InMemoryBackend;
-use bm_le::{IntoTree, FromTree, MaxVec, DigestConstruct, tree_root};
+use bm::{InMemoryBackend, ProvingBackend, Proofs, Index, DanglingRaw};
+use bm_le::{IntoTree, FromTree, TreeShape, MaxVec, DigestConstruct, tree_root, tree_root_from_iter, from_compact};
 use generic_array::GenericArray;
+use core::marker::PhantomData;
+use core::convert::TryFrom;
 
 fn chunk(data: &[u8]) -> H256 {
 	let mut ret = [0; 32];
@@ -18,7 +20,7 @@ fn h(a: &[u8], b: &[u8]) -> H256 {
 	H256::from_slice(hash.result().as_slice())
 }
 
-#[derive(IntoTree)]
+#[derive(IntoTree, FromTree, PartialEq, Eq, Debug)]
 struct BasicContainer {
 	a: u32,
 	b: u64,
@@ -37,6 +39,33 @@ struct ConfigContainer {
 	f: MaxVec<u64, typenum::U5>,
 }
 
+#[derive(IntoTree, FromTree, PartialEq, Eq, Debug)]
+struct ReorderedContainer {
+	#[bm(order = 2)]
+	c: u128,
+	#[bm(order = 0)]
+	a: u32,
+	#[bm(order = 1)]
+	b: u64,
+}
+
+#[derive(IntoTree, FromTree, PartialEq, Eq, Debug)]
+struct ReorderedTuple(#[bm(order = 1)] u64, #[bm(order = 0)] u32);
+
+#[derive(IntoTree, FromTree, PartialEq, Eq, Debug)]
+struct MarkerContainer<C> {
+	a: u64,
+	marker: PhantomData<C>,
+}
+
+#[derive(IntoTree, FromTree, PartialEq, Eq, Debug)]
+struct MarkedBasicContainer<C> {
+	a: u32,
+	marker: PhantomData<C>,
+	b: u64,
+	c: u128,
+}
+
 #[derive(IntoTree, FromTree, Debug, Eq, PartialEq)]
 pub enum EnumTest {
 	A(u128),
@@ -54,6 +83,71 @@ fn test_basic() {
 				 &h(&chunk(&[0x03])[..], &chunk(&[])[..])[..]));
 }
 
+#[test]
+fn test_field_accessors() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let container = BasicContainer { a: 1, b: 2, c: 3 };
+	assert_eq!(BasicContainer::a_gindex(), Index::root().left().left());
+	assert_eq!(BasicContainer::c_gindex(), Index::root().right().left());
+	assert_eq!(container.c_root(&mut db).unwrap(), 3u128.into_tree(&mut db).unwrap());
+
+	let container = ConfigContainer {
+		a: 1,
+		b: 2,
+		c: 3,
+		d: GenericArray::from([4, 5, 6, 7]),
+		e: 8,
+		f: MaxVec::try_from(vec![9, 10]).unwrap(),
+	};
+	let root = container.into_tree(&mut db).unwrap();
+	let raw = DanglingRaw::<DigestConstruct<Sha256>>::new(root);
+	assert_eq!(ConfigContainer::f_gindex(), Index::from_one(13).unwrap());
+	assert_eq!(raw.get(&mut db, ConfigContainer::d_gindex()).unwrap(), Some(container.d_root(&mut db).unwrap()));
+	assert_eq!(raw.get(&mut db, ConfigContainer::f_gindex()).unwrap(), Some(container.f_root(&mut db).unwrap()));
+}
+
+#[test]
+fn test_tree_root_from_iter() {
+	let items = (0..37).map(|i| BasicContainer { a: i, b: i as u64 * 2, c: i as u128 * 3 });
+	assert_eq!(tree_root_from_iter::<Sha256, _>(items.clone()),
+			   tree_root::<Sha256, _>(&items.collect::<Vec<_>>()));
+	assert_eq!(tree_root_from_iter::<Sha256, _>(Vec::<BasicContainer>::new()),
+			   tree_root::<Sha256, _>(&Vec::<BasicContainer>::new()));
+}
+
+#[derive(IntoTree, FromTree, PartialEq, Eq, Debug)]
+struct CompositeArrayContainer {
+	a: GenericArray<BasicContainer, typenum::U4>,
+	b: u64,
+}
+
+#[test]
+fn test_composite_array() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let array = GenericArray::<BasicContainer, typenum::U4>::from_exact_iter(
+		(0..4).map(|i| BasicContainer { a: i, b: i as u64 * 2, c: i as u128 * 3 })
+	).unwrap();
+	let root = array.into_tree(&mut db).unwrap();
+	let roots = array.iter().map(tree_root::<Sha256, _>).collect::<Vec<_>>();
+	assert_eq!(root.0, h(&h(&roots[0][..], &roots[1][..])[..], &h(&roots[2][..], &roots[3][..])[..]));
+	assert_eq!(GenericArray::<BasicContainer, typenum::U4>::from_tree(&root, &mut db).unwrap(), array);
+
+	let array = GenericArray::<BasicContainer, typenum::U64>::from_exact_iter(
+		(0..64).map(|i| BasicContainer { a: i, b: i as u64 * 2, c: i as u128 * 3 })
+	).unwrap();
+	let root = array.into_tree(&mut db).unwrap();
+	assert_eq!(GenericArray::<BasicContainer, typenum::U64>::from_tree(&root, &mut db).unwrap(), array);
+
+	let container = CompositeArrayContainer {
+		a: GenericArray::from_exact_iter(
+			(0..4).map(|i| BasicContainer { a: i, b: 0, c: 0 })
+		).unwrap(),
+		b: 5,
+	};
+	let root = container.into_tree(&mut db).unwrap();
+	assert_eq!(CompositeArrayContainer::from_tree(&root, &mut db).unwrap(), container);
+}
+
 #[test]
 fn test_config() {
 	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
@@ -63,13 +157,72 @@ fn test_config() {
 		c: 3,
 		d: GenericArray::from([4, 5, 6, 7]),
 		e: 8,
-		f: MaxVec::from(vec![9, 10]),
+		f: MaxVec::try_from(vec![9, 10]).unwrap(),
 	};
 	let actual = container.into_tree(&mut db).unwrap();
 	let decoded = ConfigContainer::from_tree(&actual, &mut db).unwrap();
 	assert_eq!(container, decoded);
 }
 
+#[test]
+fn test_from_compact() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let container = BasicContainer { a: 1, b: 2, c: 3 };
+	let root = container.into_tree(&mut db).unwrap();
+
+	let compact = {
+		let mut proving = ProvingBackend::new(&mut db);
+		assert_eq!(BasicContainer::from_tree(&root, &mut proving).unwrap(), container);
+		Proofs::from(proving.into_state()).into_compact(root)
+	};
+
+	assert_eq!(from_compact::<DigestConstruct<Sha256>, BasicContainer>(&compact).unwrap(), container);
+	assert!(from_compact::<DigestConstruct<Sha256>, (BasicContainer, u64)>(&compact).is_err());
+}
+
+#[test]
+fn test_shape() {
+	assert_eq!(BasicContainer::depth(), 2);
+	assert_eq!(ConfigContainer::depth(), 3);
+	assert_eq!(EnumTest::depth(), 1);
+	assert_eq!(MaxVec::<u64, typenum::U5>::depth(), 4);
+}
+
+#[test]
+fn test_field_order() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let basic = BasicContainer { a: 1, b: 2, c: 3 };
+	let reordered = ReorderedContainer { c: 3, a: 1, b: 2 };
+
+	let root = reordered.into_tree(&mut db).unwrap();
+	assert_eq!(root, basic.into_tree(&mut db).unwrap());
+	assert_eq!(ReorderedContainer::from_tree(&root, &mut db).unwrap(), reordered);
+	assert_eq!(ReorderedContainer::a_gindex(), BasicContainer::a_gindex());
+	assert_eq!(ReorderedContainer::c_gindex(), BasicContainer::c_gindex());
+
+	let tuple = ReorderedTuple(2, 1);
+	let root = tuple.into_tree(&mut db).unwrap();
+	assert_eq!(root, (1u32, 2u64).into_tree(&mut db).unwrap());
+	assert_eq!(ReorderedTuple::from_tree(&root, &mut db).unwrap(), tuple);
+}
+
+#[test]
+fn test_marker() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let container = MarkerContainer::<u32> { a: 1, marker: PhantomData };
+	let root = container.into_tree(&mut db).unwrap();
+	assert_eq!(H256::from_slice(root.as_ref()), chunk(&[0x01]));
+	assert_eq!(MarkerContainer::<u32>::from_tree(&root, &mut db).unwrap(), container);
+
+	let basic = BasicContainer { a: 1, b: 2, c: 3 };
+	let marked = MarkedBasicContainer::<String> { a: 1, marker: PhantomData, b: 2, c: 3 };
+	let root = marked.into_tree(&mut db).unwrap();
+	assert_eq!(root, basic.into_tree(&mut db).unwrap());
+	assert_eq!(MarkedBasicContainer::<String>::from_tree(&root, &mut db).unwrap(), marked);
+	assert_eq!(MarkedBasicContainer::<String>::depth(), BasicContainer::depth());
+	assert_eq!(MarkedBasicContainer::<String>::c_gindex(), BasicContainer::c_gindex());
+}
+
 #[test]
 fn test_enum() {
 	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
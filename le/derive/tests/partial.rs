@@ -1,6 +1,6 @@
-use bm_le::{FromTree, IntoTree, Partialable, DigestConstruct, PartialItem, PartialIndex, DanglingRaw};
+use bm_le::{FromTree, IntoTree, Partialable, DigestConstruct, PartialItem, PartialIndex, DanglingRaw, prove_path};
 use sha2::Sha256;
-use bm::{InMemoryBackend, Tree};
+use bm::{InMemoryBackend, Tree, Index, verify_proof};
 
 #[derive(FromTree, IntoTree, Partialable)]
 struct BasicContainer {
@@ -38,3 +38,51 @@ fn partial_test() {
 	partial.flush(&mut raw, &mut db).unwrap();
 	assert_eq!(raw.root(), new_root);
 }
+
+#[test]
+fn partial_modify_test() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let mut full = NestedContainer {
+		basic: BasicContainer {
+			a: 1,
+			b: 2,
+			c: 3,
+		},
+		d: 4,
+	};
+	let root = full.into_tree(&mut db).unwrap();
+	full.basic.b += 10;
+	let new_root = full.into_tree(&mut db).unwrap();
+
+	let mut raw = DanglingRaw::<DigestConstruct<Sha256>>::new(root);
+	let mut partial = PartialNestedContainer::new(PartialIndex::root());
+	partial.basic.b.modify(&raw, &mut db, |b| *b += 10).unwrap();
+	assert_eq!(*partial.basic.b.get(&raw, &mut db).unwrap(), 12);
+
+	partial.flush(&mut raw, &mut db).unwrap();
+	assert_eq!(raw.root(), new_root);
+}
+
+#[test]
+fn prove_path_test() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let full = NestedContainer {
+		basic: BasicContainer {
+			a: 1,
+			b: 2,
+			c: 3,
+		},
+		d: 4,
+	};
+	let root = full.into_tree(&mut db).unwrap();
+	let raw = DanglingRaw::<DigestConstruct<Sha256>>::new(root.clone());
+
+	let (index, leaf, proofs) = prove_path::<NestedContainer, _, _>(&raw, &["basic", "c"], &mut db).unwrap();
+	assert_eq!(index, Index::from_one(3).unwrap().sub(Index::from_one(6).unwrap()));
+	assert_eq!(leaf, 3u128.into_tree(&mut db).unwrap());
+	assert_eq!(proofs.len(), 3);
+	assert!(verify_proof::<DigestConstruct<Sha256>, _>(&root, leaf.clone(), proofs.clone(), index));
+	assert!(!verify_proof::<DigestConstruct<Sha256>, _>(&root, 5u128.into_tree(&mut db).unwrap(), proofs, index));
+
+	assert!(prove_path::<NestedContainer, _, _>(&raw, &["basic", "e"], &mut db).is_err());
+}
@@ -1,8 +1,11 @@
 use bm::{ReadBackend, WriteBackend, Construct, Error, Index, DanglingRaw, Leak};
-use primitive_types::{H256, U256};
+use primitive_types::{H256, U256, U512};
 use alloc::boxed::Box;
+use core::time::Duration;
+use core::marker::PhantomData;
 
-use crate::{IntoTree, FromTree, Value, CompatibleConstruct};
+use crate::{IntoTree, FromTree, TreeShape, Value, CompatibleConstruct, BigEndian, Fixed,
+			ElementalFixedVecRef, ElementalFixedVec, IntoCompactVectorTree, FromCompactVectorTree};
 use crate::utils::{mix_in_type, decode_with_type};
 
 impl IntoTree for bool {
@@ -59,7 +62,31 @@ macro_rules! impl_builtin_uint {
 	)* }
 }
 
-impl_builtin_uint!(u8, u16, u32, u64, u128);
+// Signed integers use the same little-endian encoding, which is two's
+// complement.
+impl_builtin_uint!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+macro_rules! impl_chunk_shape {
+	( $( $t:ty ),* ) => { $(
+		impl TreeShape for $t {
+			fn depth() -> usize { 0 }
+		}
+	)* }
+}
+
+impl_chunk_shape!(bool, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, U256, Value, BigEndian<U256>);
+
+impl<const SCALE: u32> TreeShape for Fixed<SCALE> {
+	fn depth() -> usize { 0 }
+}
+
+impl TreeShape for U512 {
+	fn depth() -> usize { 1 }
+}
+
+impl TreeShape for BigEndian<U512> {
+	fn depth() -> usize { 1 }
+}
 
 impl IntoTree for U256 {
 	fn into_tree<DB: WriteBackend>(&self, _db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
@@ -122,6 +149,92 @@ impl IntoTree for bm::CompactValue<Value> {
 	}
 }
 
+impl<const SCALE: u32> IntoTree for Fixed<SCALE> {
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		self.0.into_tree(db)
+	}
+}
+
+impl<const SCALE: u32> FromTree for Fixed<SCALE> {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		i128::from_tree(root, db).map(Fixed)
+	}
+}
+
+impl IntoTree for BigEndian<U256> {
+	fn into_tree<DB: WriteBackend>(&self, _db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let mut ret = [0u8; 32];
+		self.0.to_big_endian(&mut ret);
+
+		Ok(Value(H256::from(ret)))
+	}
+}
+
+impl FromTree for BigEndian<U256> {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let raw = DanglingRaw::from_leaked(root.clone());
+
+		match raw.get(db, Index::root())? {
+			None => Err(Error::CorruptedDatabase),
+			Some(value) => {
+				Ok(BigEndian(U256::from_big_endian(value.as_ref())))
+			},
+		}
+	}
+}
+
+impl IntoTree for U512 {
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let mut ret = [0u8; 64];
+		self.to_little_endian(&mut ret);
+
+		ElementalFixedVecRef(&ret[..]).into_compact_vector_tree(db, None)
+	}
+}
+
+impl FromTree for U512 {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let value = ElementalFixedVec::<u8>::from_compact_vector_tree(root, db, 64, None)?;
+		Ok(U512::from_little_endian(&value.0))
+	}
+}
+
+impl IntoTree for BigEndian<U512> {
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let mut ret = [0u8; 64];
+		self.0.to_big_endian(&mut ret);
+
+		ElementalFixedVecRef(&ret[..]).into_compact_vector_tree(db, None)
+	}
+}
+
+impl FromTree for BigEndian<U512> {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let value = ElementalFixedVec::<u8>::from_compact_vector_tree(root, db, 64, None)?;
+		Ok(BigEndian(U512::from_big_endian(&value.0)))
+	}
+}
+
+impl<T> TreeShape for Option<T> {
+	fn depth() -> usize { 1 }
+}
+
 impl<T> FromTree for Option<T> where
 	T: FromTree,
 {
@@ -154,6 +267,53 @@ impl<T> IntoTree for Option<T> where
 	}
 }
 
+impl<T, E> TreeShape for Result<T, E> {
+	fn depth() -> usize { 1 }
+}
+
+impl<T, E> FromTree for Result<T, E> where
+	T: FromTree,
+	E: FromTree,
+{
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		decode_with_type(root, db, |inner, db, ty| {
+			match ty {
+				0 => Ok(Ok(T::from_tree(inner, db)?)),
+				1 => Ok(Err(E::from_tree(inner, db)?)),
+				_ => Err(Error::CorruptedDatabase),
+			}
+		})
+	}
+}
+
+impl<T, E> IntoTree for Result<T, E> where
+	T: IntoTree,
+	E: IntoTree,
+{
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		match self {
+			Ok(value) => mix_in_type(value, db, 0),
+			Err(err) => mix_in_type(err, db, 1),
+		}
+	}
+}
+
+impl<T: TreeShape> TreeShape for Box<T> {
+	fn depth() -> usize { T::depth() }
+}
+
+impl<T: IntoTree + ?Sized> IntoTree for &T {
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		(*self).into_tree(db)
+	}
+}
+
 impl<T> FromTree for Box<T> where
 	T: FromTree,
 {
@@ -174,6 +334,56 @@ impl<T> IntoTree for Box<T> where
 	}
 }
 
+impl TreeShape for Duration {
+	fn depth() -> usize { 1 }
+}
+
+impl IntoTree for Duration {
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		(self.as_secs(), self.subsec_nanos()).into_tree(db)
+	}
+}
+
+impl FromTree for Duration {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let (secs, nanos) = <(u64, u32)>::from_tree(root, db)?;
+
+		if nanos >= 1_000_000_000 {
+			return Err(Error::CorruptedDatabase)
+		}
+
+		Ok(Duration::new(secs, nanos))
+	}
+}
+
+impl<T> TreeShape for PhantomData<T> {
+	fn depth() -> usize { 0 }
+}
+
+impl<T> IntoTree for PhantomData<T> {
+	fn into_tree<DB: WriteBackend>(&self, _db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		Ok(Default::default())
+	}
+}
+
+impl<T> FromTree for PhantomData<T> {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, _db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		if root != &DB::Construct::empty_end() {
+			return Err(Error::CorruptedDatabase)
+		}
+
+		Ok(PhantomData)
+	}
+}
+
 /// Representing an ignored value.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Ignored;
@@ -1,5 +1,5 @@
 use bm::{ReadBackend, WriteBackend, Construct, Error, DanglingPackedVector, DanglingVector, Leak, Sequence};
-use bm::utils::{vector_tree, host_max_len};
+use bm::utils::{vector_tree, host_max_len, VectorTreeBuilder};
 use primitive_types::{H256, U256};
 use generic_array::GenericArray;
 use alloc::vec::Vec;
@@ -128,6 +128,11 @@ impl_builtin_fixed_uint_vector!(u16, typenum::U2);
 impl_builtin_fixed_uint_vector!(u32, typenum::U4);
 impl_builtin_fixed_uint_vector!(u64, typenum::U8);
 impl_builtin_fixed_uint_vector!(u128, typenum::U16);
+impl_builtin_fixed_uint_vector!(i8, typenum::U1);
+impl_builtin_fixed_uint_vector!(i16, typenum::U2);
+impl_builtin_fixed_uint_vector!(i32, typenum::U4);
+impl_builtin_fixed_uint_vector!(i64, typenum::U8);
+impl_builtin_fixed_uint_vector!(i128, typenum::U16);
 
 impl<'a> IntoCompactVectorTree for ElementalFixedVecRef<'a, U256> {
 	fn into_compact_vector_tree<DB: WriteBackend>(
@@ -226,9 +231,12 @@ impl<'a, T> IntoCompositeVectorTree for ElementalFixedVecRef<'a, T> where
 	) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
 		DB::Construct: CompatibleConstruct,
 	{
-		vector_tree(&self.0.iter().map(|value| {
-			value.into_tree(db)
-		}).collect::<Result<Vec<_>, _>>()?, db, max_len)
+		let mut builder = VectorTreeBuilder::new(max_len);
+		for value in self.0 {
+			let root = value.into_tree(db)?;
+			builder.push(db, root)?;
+		}
+		builder.finish(db)
 	}
 }
 
@@ -5,7 +5,7 @@ use alloc::vec::Vec;
 use crate::{ElementalFixedVec, FromCompactVectorTree, FromCompositeVectorTree,
 			ElementalFixedVecRef, IntoCompactVectorTree,
 			IntoCompositeVectorTree, CompatibleConstruct};
-use crate::utils::{mix_in_length, decode_with_length};
+use crate::utils::{mix_in_length, decode_with_length_u64, narrow_length};
 
 /// Traits for list converting into a tree structure.
 pub trait IntoCompositeListTree {
@@ -62,6 +62,13 @@ pub struct ElementalVariableVecRef<'a, T>(pub &'a [T]);
 /// Variable `Vec` value. In `ssz`'s definition, this is a "list".
 pub struct ElementalVariableVec<T>(pub Vec<T>);
 
+fn check_max_len<E>(len: usize, max_len: Option<u64>) -> Result<(), Error<E>> {
+	match max_len {
+		Some(max_len) if len as u64 > max_len => Err(Error::InvalidParameter),
+		_ => Ok(()),
+	}
+}
+
 macro_rules! impl_packed {
 	( $t:ty ) => {
 		impl<'a> IntoCompactListTree for ElementalVariableVecRef<'a, $t> {
@@ -73,6 +80,7 @@ macro_rules! impl_packed {
 				DB::Construct: CompatibleConstruct,
 			{
 				let len = self.0.len();
+				check_max_len(len, max_len)?;
 
 				mix_in_length(&ElementalFixedVecRef(&self.0).into_compact_vector_tree(db, max_len)?,
 							  db, len)
@@ -87,6 +95,11 @@ impl_packed!(u16);
 impl_packed!(u32);
 impl_packed!(u64);
 impl_packed!(u128);
+impl_packed!(i8);
+impl_packed!(i16);
+impl_packed!(i32);
+impl_packed!(i64);
+impl_packed!(i128);
 impl_packed!(U256);
 
 impl<'a, T> IntoCompositeListTree for ElementalVariableVecRef<'a, T> where
@@ -100,6 +113,7 @@ impl<'a, T> IntoCompositeListTree for ElementalVariableVecRef<'a, T> where
 		DB::Construct: CompatibleConstruct,
 	{
 		let len = self.0.len();
+		check_max_len(len, max_len)?;
 
 		mix_in_length(&ElementalFixedVecRef(&self.0).into_composite_vector_tree(db, max_len)?,
 					  db, len)
@@ -115,10 +129,16 @@ fn from_list_tree<T, F, DB: ReadBackend>(
 	DB::Construct: CompatibleConstruct,
 	F: FnOnce(&<DB::Construct as Construct>::Value, &mut DB, usize, Option<u64>) -> Result<ElementalFixedVec<T>, Error<DB::Error>>
 {
-	let (vector_root, len) = decode_with_length::<<DB::Construct as Construct>::Value, _>(root, db)?;
+	let (vector_root, len) = decode_with_length_u64::<<DB::Construct as Construct>::Value, _>(root, db)?;
+
+	if let Some(max_len) = max_len {
+		if len > max_len {
+			return Err(Error::CorruptedDatabase)
+		}
+	}
 
 	let vector = f(
-		&vector_root, db, len, max_len
+		&vector_root, db, narrow_length(len)?, max_len
 	)?;
 
 	Ok(ElementalVariableVec(vector.0))
@@ -194,6 +214,7 @@ mod tests {
 	use crate::{IntoTree, FromTree, DigestConstruct};
 
 	use bm::InMemoryBackend;
+	use primitive_types::H256;
 	use sha2::Sha256;
 
 	#[test]
@@ -211,4 +232,70 @@ mod tests {
 		let decoded = Vec::<u16>::from_tree(&encoded, &mut db).unwrap();
 		assert_eq!(data, decoded);
 	}
+
+	#[test]
+	fn test_options() {
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+
+		let data = vec![Some(1u64), None, Some(u64::MAX), None, None, Some(0)];
+		let composite = ElementalVariableVecRef(&data).into_composite_list_tree(&mut db, Some(8)).unwrap();
+		assert_eq!(ElementalVariableVec::<Option<u64>>::from_composite_list_tree(&composite, &mut db, Some(8)).unwrap().0, data);
+		assert_eq!(composite, data.into_tree(&mut db).unwrap());
+		assert_eq!(Vec::<Option<u64>>::from_tree(&composite, &mut db).unwrap(), data);
+
+		let data = vec![None, Some(H256::repeat_byte(1)), None, Some(H256::zero())];
+		let composite = ElementalVariableVecRef(&data).into_composite_list_tree(&mut db, None).unwrap();
+		assert_eq!(ElementalVariableVec::<Option<H256>>::from_composite_list_tree(&composite, &mut db, None).unwrap().0, data);
+		assert_eq!(Vec::<Option<H256>>::from_tree(&composite, &mut db).unwrap(), data);
+	}
+
+	#[test]
+	fn test_max_len() {
+		let data = (0..17u16).collect::<Vec<_>>();
+
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		let compact = ElementalVariableVecRef(&data).into_compact_list_tree(&mut db, Some(32)).unwrap();
+		let composite = ElementalVariableVecRef(&data).into_composite_list_tree(&mut db, Some(32)).unwrap();
+
+		assert_eq!(ElementalVariableVec::<u16>::from_compact_list_tree(&compact, &mut db, Some(32)).unwrap().0, data);
+		assert_eq!(ElementalVariableVec::<u16>::from_composite_list_tree(&composite, &mut db, Some(32)).unwrap().0, data);
+		assert_eq!(ElementalVariableVec::<u16>::from_compact_list_tree(&compact, &mut db, Some(16)), Err(Error::CorruptedDatabase));
+		assert_eq!(ElementalVariableVec::<u16>::from_composite_list_tree(&composite, &mut db, Some(16)), Err(Error::CorruptedDatabase));
+	}
+
+	#[test]
+	fn test_large_declared_length() {
+		let data = (0..17u16).collect::<Vec<_>>();
+
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		let vector = ElementalFixedVecRef(&data).into_compact_vector_tree(&mut db, Some(32)).unwrap();
+		let large = (vector, primitive_types::U256::from(u64::MAX)).into_tree(&mut db).unwrap();
+
+		assert_eq!(ElementalVariableVec::<u16>::from_compact_list_tree(&large, &mut db, Some(32)), Err(Error::CorruptedDatabase));
+		if (usize::MAX as u64) < u64::MAX {
+			assert_eq!(ElementalVariableVec::<u16>::from_compact_list_tree(&large, &mut db, None), Err(Error::AccessOverflowed));
+		}
+	}
+
+	#[test]
+	fn test_composite_iter() {
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		for &len in &[0u64, 1, 2, 5, 16, 17] {
+			let data = (0..len).collect::<Vec<_>>();
+			assert_eq!(
+				crate::utils::into_composite_list_tree_iter(data.iter(), &mut db, None).unwrap(),
+				data.into_tree(&mut db).unwrap(),
+			);
+			assert_eq!(
+				crate::utils::into_composite_list_tree_iter(data.iter(), &mut db, Some(32)).unwrap(),
+				crate::utils::mix_in_length(
+					&crate::utils::vector_tree(
+						&data.iter().map(|v| v.into_tree(&mut db).unwrap()).collect::<Vec<_>>(),
+						&mut db, Some(32)
+					).unwrap(),
+					&mut db, len as usize
+				).unwrap(),
+			);
+		}
+	}
 }
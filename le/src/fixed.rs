@@ -1,13 +1,13 @@
 use bm::{ReadBackend, WriteBackend, Construct, Error, DanglingVector, Leak};
-use bm::utils::vector_tree;
-use primitive_types::{H256, H512};
+use bm::utils::{vector_tree, required_depth};
+use primitive_types::{H160, H256, H512};
 use generic_array::{GenericArray, ArrayLength};
 use vecarray::VecArray;
 use typenum::Unsigned;
 use core::convert::TryFrom;
 use alloc::vec::Vec;
 use crate::{ElementalFixedVecRef, ElementalFixedVec, IntoCompositeVectorTree,
-			IntoCompactVectorTree, IntoTree, FromTree, FromCompositeVectorTree,
+			IntoCompactVectorTree, IntoTree, FromTree, TreeShape, FromCompositeVectorTree,
 			FromCompactVectorTree, Compact, CompactRef, CompatibleConstruct};
 
 impl<'a, T, L: ArrayLength<T>> IntoTree for CompactRef<'a, GenericArray<T, L>> where
@@ -78,42 +78,101 @@ impl<T, L: Unsigned> FromTree for Compact<VecArray<T, L>> where
 	}
 }
 
-impl IntoTree for H256 {
-	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
-		DB::Construct: CompatibleConstruct,
-	{
-		ElementalFixedVecRef(&self.0.as_ref()).into_compact_vector_tree(db, None)
-	}
-}
+macro_rules! construct_hash {
+	( $( $(#[$attr:meta])* $name:ident($n:expr); )* ) => { $(
+		$(#[$attr])*
+		#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+		pub struct $name(pub [u8; $n]);
 
-impl FromTree for H256 {
-	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
-		DB::Construct: CompatibleConstruct,
-	{
-		let value = ElementalFixedVec::<u8>::from_compact_vector_tree(root, db, 32, None)?;
-		Ok(Self::from_slice(value.0.as_ref()))
-	}
+		impl $name {
+			/// Hash with all bytes zero.
+			pub fn zero() -> Self {
+				$name([0u8; $n])
+			}
+
+			/// Create the hash from a byte slice. Panics if the slice length
+			/// does not match.
+			pub fn from_slice(src: &[u8]) -> Self {
+				let mut ret = Self::zero();
+				ret.0.copy_from_slice(src);
+				ret
+			}
+
+			/// Bytes of the hash.
+			pub fn as_bytes(&self) -> &[u8] {
+				&self.0
+			}
+		}
+
+		impl Default for $name {
+			fn default() -> Self {
+				Self::zero()
+			}
+		}
+
+		impl From<[u8; $n]> for $name {
+			fn from(bytes: [u8; $n]) -> Self {
+				$name(bytes)
+			}
+		}
+
+		impl AsRef<[u8]> for $name {
+			fn as_ref(&self) -> &[u8] {
+				&self.0
+			}
+		}
+
+		impl AsMut<[u8]> for $name {
+			fn as_mut(&mut self) -> &mut [u8] {
+				&mut self.0
+			}
+		}
+	)* }
 }
 
-impl IntoTree for H512 {
-	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
-		DB::Construct: CompatibleConstruct,
-	{
-		ElementalFixedVecRef(&self.0.as_ref()).into_compact_vector_tree(db, None)
-	}
+// Hash types not provided by `primitive-types`.
+construct_hash! {
+	/// Fixed-size uninterpreted hash type with 16 bytes (128 bits) size.
+	H128(16);
+	/// Fixed-size uninterpreted hash type with 48 bytes (384 bits) size.
+	H384(48);
+	/// Fixed-size uninterpreted hash type with 96 bytes (768 bits) size.
+	H768(96);
 }
 
-impl FromTree for H512 {
-	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
-		DB::Construct: CompatibleConstruct,
-	{
-		let value = ElementalFixedVec::<u8>::from_compact_vector_tree(root, db, 32, None)?;
-		Ok(Self::from_slice(value.0.as_ref()))
-	}
+macro_rules! impl_fixed_hash {
+	( $( $t:ty: $n:expr, $chunks:expr );* ) => { $(
+		impl TreeShape for $t {
+			fn depth() -> usize { required_depth($chunks) }
+		}
+
+		impl IntoTree for $t {
+			fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+				DB::Construct: CompatibleConstruct,
+			{
+				ElementalFixedVecRef(self.as_bytes()).into_compact_vector_tree(db, None)
+			}
+		}
+
+		impl FromTree for $t {
+			fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+				DB::Construct: CompatibleConstruct,
+			{
+				let value = ElementalFixedVec::<u8>::from_compact_vector_tree(root, db, $n, None)?;
+				Ok(Self::from_slice(value.0.as_ref()))
+			}
+		}
+	)* }
 }
 
+impl_fixed_hash!(H128: 16, 1; H160: 20, 1; H256: 32, 1; H384: 48, 2; H512: 64, 2; H768: 96, 3);
+
 macro_rules! impl_fixed_array {
 	( $( $n:expr ),* ) => { $(
+		impl<T> TreeShape for [T; $n] {
+			fn depth() -> usize { required_depth($n) }
+		}
+
 		impl<T> IntoTree for [T; $n] where
 			for<'a> ElementalFixedVecRef<'a, T>: IntoCompositeVectorTree,
 		{
@@ -147,6 +206,10 @@ impl_fixed_array!(1, 2, 3, 4, 5, 6, 7, 8,
 				  17, 18, 19, 20, 21, 22, 23, 24,
 				  25, 26, 27, 28, 29, 30, 31, 32);
 
+impl<T, L: ArrayLength<T>> TreeShape for GenericArray<T, L> {
+	fn depth() -> usize { required_depth(L::to_u64()) }
+}
+
 impl<T, L: ArrayLength<T>> IntoTree for GenericArray<T, L> where
 	for<'a> ElementalFixedVecRef<'a, T>: IntoCompositeVectorTree,
 {
@@ -169,6 +232,10 @@ impl<T, L: ArrayLength<T>> FromTree for GenericArray<T, L> where
 	}
 }
 
+impl<T, L: Unsigned> TreeShape for VecArray<T, L> {
+	fn depth() -> usize { required_depth(L::to_u64()) }
+}
+
 impl<T, L: Unsigned> IntoTree for VecArray<T, L> where
 	for<'a> ElementalFixedVecRef<'a, T>: IntoCompositeVectorTree,
 {
@@ -190,11 +257,15 @@ impl<T, L: Unsigned> FromTree for VecArray<T, L> where
 	}
 }
 
+impl TreeShape for () {
+	fn depth() -> usize { 0 }
+}
+
 impl FromTree for () {
 	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, _db: &mut DB) -> Result<Self, Error<DB::Error>> where
 		DB::Construct: CompatibleConstruct,
 	{
-		if root == &Default::default() {
+		if root == &DB::Construct::empty_end() {
 			Ok(())
 		} else {
 			Err(Error::CorruptedDatabase)
@@ -212,6 +283,10 @@ impl IntoTree for () {
 
 macro_rules! impl_tuple {
 	($len:expr, $($i:ident => $t:ident),+) => {
+		impl<$($t),+> TreeShape for ($($t,)+) {
+			fn depth() -> usize { required_depth($len) }
+		}
+
 		impl<$($t: FromTree),+> FromTree for ($($t,)+) {
 			fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
 				DB::Construct: CompatibleConstruct,
@@ -245,6 +320,8 @@ macro_rules! impl_tuple {
 	}
 }
 
+// Nested pairs terminated by `()`, as in `(A, (B, (C, ())))`, merkleize as a
+// right-leaning chain.
 impl_tuple!(2, a => A, b => B);
 impl_tuple!(3, a => A, b => B, c => C);
 impl_tuple!(4, a => A, b => B, c => C, d => D);
@@ -253,3 +330,8 @@ impl_tuple!(6, a => A, b => B, c => C, d => D, e => E, f => F);
 impl_tuple!(7, a => A, b => B, c => C, d => D, e => E, f => F, g => G);
 impl_tuple!(8, a => A, b => B, c => C, d => D, e => E, f => F, g => G, h => H);
 impl_tuple!(9, a => A, b => B, c => C, d => D, e => E, f => F, g => G, h => H, i => I);
+impl_tuple!(10, a => A, b => B, c => C, d => D, e => E, f => F, g => G, h => H, i => I, j => J);
+impl_tuple!(11, a => A, b => B, c => C, d => D, e => E, f => F, g => G, h => H, i => I, j => J,
+			k => K);
+impl_tuple!(12, a => A, b => B, c => C, d => D, e => E, f => F, g => G, h => H, i => I, j => J,
+			k => K, l => L);
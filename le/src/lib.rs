@@ -16,7 +16,7 @@ use digest::Digest;
 pub use bm::{Backend, ReadBackend, WriteBackend, InheritedDigestConstruct,
 			 UnitDigestConstruct, Construct, InheritedEmpty, Error, Vector,
 			 DanglingVector, List, Leak, NoopBackend, InMemoryBackend, Raw,
-			 RootStatus, OwnedRaw, DanglingRaw};
+			 RootStatus, OwnedRaw, DanglingRaw, CompactValue, CompactBackend, Index};
 
 mod basic;
 mod elemental_fixed;
@@ -33,8 +33,9 @@ pub use elemental_fixed::{ElementalFixedVec, ElementalFixedVecRef,
 pub use elemental_variable::{ElementalVariableVec, ElementalVariableVecRef,
 							 IntoCompactListTree, FromCompactListTree,
 							 IntoCompositeListTree, FromCompositeListTree};
+pub use fixed::{H128, H384, H768};
 pub use variable::MaxVec;
-pub use partial::{PartialIndex, PartialValue, PartialVec, PartialItem, Partialable};
+pub use partial::{PartialIndex, PartialValue, PartialVec, PartialItem, Partialable, PathProof, prove_path};
 #[cfg(feature = "derive")]
 pub use bm_le_derive::{FromTree, IntoTree, Partialable};
 
@@ -118,6 +119,14 @@ pub trait FromTree: Sized {
 		DB::Construct: CompatibleConstruct;
 }
 
+/// Static shape of the merkle tree of a type.
+pub trait TreeShape {
+	/// Depth from the root of the type's tree down to the chunks of its
+	/// own fields or items, not descending into them. Lists count one
+	/// extra level for the mixed-in length.
+	fn depth() -> usize;
+}
+
 /// Indicate that the current value should be serialized and
 /// deserialized in Compact format. Reference form.
 #[derive(Debug, Eq, PartialEq)]
@@ -136,6 +145,28 @@ impl<T> From<T> for Compact<T> {
 	}
 }
 
+/// Indicate that the current value should be serialized and
+/// deserialized in big-endian byte order, instead of the default
+/// little-endian.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct BigEndian<T>(pub T);
+
+impl<T> From<T> for BigEndian<T> {
+	fn from(t: T) -> Self {
+		Self(t)
+	}
+}
+
+/// Fixed-point number with `SCALE` decimal places, stored as its raw
+/// `i128` mantissa. Only the mantissa is merkleized, so roots do not encode
+/// the scale, which is carried in the type.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Fixed<const SCALE: u32>(pub i128);
+
 /// Calculate a ssz merkle tree root, dismissing the tree.
 pub fn tree_root<D, T>(value: &T) -> H256 where
 	T: IntoTree,
@@ -145,3 +176,38 @@ pub fn tree_root<D, T>(value: &T) -> H256 where
 		.map(|ret| H256::from_slice(ret.as_ref()))
 		.expect("Noop backend never fails in set; qed")
 }
+
+/// Calculate a ssz merkle tree root of a composite list whose items are
+/// produced by an iterator, without collecting them first. The result is
+/// the same as `tree_root` of the collected `Vec` for composite items.
+pub fn tree_root_from_iter<D, I>(iter: I) -> H256 where
+	I: IntoIterator,
+	I::Item: IntoTree,
+	D: Digest<OutputSize=U32>,
+{
+	utils::into_composite_list_tree_iter(iter, &mut NoopBackend::<DigestConstruct<D>>::default(), None)
+		.map(|ret| H256::from_slice(ret.as_ref()))
+		.expect("Noop backend never fails in set; qed")
+}
+
+/// Calculate a ssz merkle tree root of the SCALE encoding of a value,
+/// merkleized as a packed byte list. The root is over the encoded bytes,
+/// not a structural merkleization of the value.
+#[cfg(feature = "parity-codec")]
+pub fn encode_tree_root<D, T>(value: &T) -> H256 where
+	T: parity_codec::Encode,
+	D: Digest<OutputSize=U32>,
+{
+	tree_root::<D, _>(&Compact(value.encode()))
+}
+
+/// Decode a value directly from its compact proof, without building a
+/// backend. Nodes missing from the proof result in `CorruptedDatabase`.
+pub fn from_compact<C, T>(compact: &CompactValue<Value>) -> Result<T, Error<()>> where
+	C: CompatibleConstruct,
+	T: FromTree,
+{
+	let mut db = CompactBackend::<C>::new(compact);
+	let root = db.root();
+	T::from_tree(&root, &mut db)
+}
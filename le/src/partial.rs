@@ -1,11 +1,12 @@
-use bm::{Index, Error, ReadBackend, RootStatus, Raw, DanglingList, Tree, WriteBackend};
-use primitive_types::{U256, H256};
+use bm::{Index, IndexRoute, IndexSelection, Error, ReadBackend, RootStatus, Raw, DanglingList, Tree, WriteBackend, Construct};
+use primitive_types::{U256, H160, H256, H512};
 use core::mem;
 #[cfg(not(feature = "std"))]
 use alloc::collections::BTreeMap as Map;
 #[cfg(feature = "std")]
 use std::collections::HashMap as Map;
-use crate::{FromTree, IntoTree, CompatibleConstruct};
+use alloc::vec::Vec;
+use crate::{FromTree, IntoTree, CompatibleConstruct, H128, H384, H768};
 
 /// Partial index for le binary tree.
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -142,6 +143,23 @@ impl<T: FromTree> PartialValue<T> {
 	pub fn set(&mut self, value: T) {
 		self.value = Some(value);
 	}
+
+	/// Modify the partial value in place, fetching it first if needed.
+	pub fn modify<R: RootStatus, DB: ReadBackend, F: FnOnce(&mut T)>(
+		&mut self,
+		raw: &Raw<R, DB::Construct>,
+		db: &mut DB,
+		f: F,
+	) -> Result<(), Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct
+	{
+		if self.value.is_none() {
+			self.fetch(raw, db)?;
+		}
+
+		f(self.value.as_mut().expect("value is checked to be some or set before; qed"));
+		Ok(())
+	}
 }
 
 impl<T: IntoTree> PartialItem for PartialValue<T> {
@@ -245,6 +263,54 @@ pub trait PartialItem {
 pub trait Partialable {
 	/// Value type of the partial item.
 	type Value: PartialItem;
+
+	/// Resolve a field path beneath the given partial index. Returns
+	/// `None` if the path does not exist.
+	fn partial_path(index: PartialIndex, path: &[&str]) -> Option<PartialIndex> {
+		if path.is_empty() {
+			Some(index)
+		} else {
+			None
+		}
+	}
+}
+
+/// Proof of a field path: the field's index, its value, and sibling values
+/// ordered from the field up to the root.
+pub type PathProof<V> = (Index, V, Vec<V>);
+
+/// Prove a nested field path of a partialable value.
+pub fn prove_path<T: Partialable, R: RootStatus, DB: ReadBackend>(
+	raw: &Raw<R, DB::Construct>,
+	path: &[&str],
+	db: &mut DB,
+) -> Result<PathProof<<DB::Construct as Construct>::Value>, Error<DB::Error>> where
+	DB::Construct: CompatibleConstruct,
+{
+	let index = T::partial_path(PartialIndex::root(), path)
+		.ok_or(Error::InvalidParameter)?
+		.resolve(raw, db)?;
+
+	let mut current = raw.root();
+	let mut proofs = Vec::new();
+	if let IndexRoute::Select(selections) = index.route() {
+		for selection in selections {
+			let (left, right) = db.get(&current)?.ok_or(Error::CorruptedDatabase)?;
+			current = match selection {
+				IndexSelection::Left => {
+					proofs.push(right);
+					left
+				},
+				IndexSelection::Right => {
+					proofs.push(left);
+					right
+				},
+			};
+		}
+	}
+	proofs.reverse();
+
+	Ok((index, current, proofs))
 }
 
 macro_rules! basic_partialables {
@@ -255,4 +321,4 @@ macro_rules! basic_partialables {
 	)* }
 }
 
-basic_partialables!(u8, u16, u32, u64, u128, U256, H256);
+basic_partialables!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, U256, H128, H160, H256, H384, H512, H768);
@@ -1,22 +1,55 @@
 //! Utilities
 
 use bm::{ReadBackend, WriteBackend, Construct, Error};
-use primitive_types::U256;
-use crate::{CompatibleConstruct, IntoTree, FromTree};
+use primitive_types::{U256, H256};
+use digest::Digest;
+use typenum::U32;
+use alloc::vec::Vec;
+use crate::{CompatibleConstruct, IntoTree, FromTree, IntoCompactListTree, ElementalVariableVecRef};
 
 pub use bm::utils::*;
 
-/// Mix in type.
-pub fn mix_in_type<T, DB: WriteBackend>(value: &T, db: &mut DB, ty: usize) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+/// Roots of all-zero subtrees from depth `0` up to and including
+/// `max_depth`, without a backend. The first item is the zero chunk, and
+/// each following one is the hash of two copies of the previous.
+pub fn zero_hashes<D>(max_depth: usize) -> Vec<H256> where
+	D: Digest<OutputSize=U32>,
+{
+	let mut ret = Vec::with_capacity(max_depth + 1);
+	let mut current = H256::default();
+	ret.push(current);
+
+	for _ in 0..max_depth {
+		let mut digest = D::new();
+		digest.input(current.as_ref());
+		digest.input(current.as_ref());
+		current = H256::from_slice(digest.result().as_slice());
+		ret.push(current);
+	}
+
+	ret
+}
+
+/// Mix in auxiliary data, as the right child of the value.
+pub fn mix_in<T, A, DB: WriteBackend>(value: &T, db: &mut DB, aux: &A) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
 	T: IntoTree,
+	A: IntoTree,
 	DB::Construct: CompatibleConstruct,
 {
 	let left = value.into_tree(db)?;
-	let right = U256::from(ty).into_tree(db)?;
+	let right = aux.into_tree(db)?;
 
 	(left, right).into_tree(db)
 }
 
+/// Mix in type.
+pub fn mix_in_type<T, DB: WriteBackend>(value: &T, db: &mut DB, ty: usize) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+	T: IntoTree,
+	DB::Construct: CompatibleConstruct,
+{
+	mix_in(value, db, &U256::from(ty))
+}
+
 /// Decode type.
 pub fn decode_with_type<DB: ReadBackend, F, R>(root: &<DB::Construct as Construct>::Value, db: &mut DB, f: F) -> Result<R, Error<DB::Error>> where
 	F: FnOnce(&<DB::Construct as Construct>::Value, &mut DB, usize) -> Result<R, Error<DB::Error>>,
@@ -31,15 +64,73 @@ pub fn decode_with_type<DB: ReadBackend, F, R>(root: &<DB::Construct as Construc
 	}
 }
 
+/// Position of the length node when mixing in length.
+///
+/// SSZ, in all of its published versions, mixes the length in as the
+/// right child, which is the default. `Left` is only for reproducing
+/// non-standard layouts that put the length first.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum MixPosition {
+	/// Length is the left child, and the value is the right child.
+	Left,
+	/// Length is the right child, and the value is the left child.
+	#[default]
+	Right,
+}
+
 /// Mix in length.
 pub fn mix_in_length<T, DB: WriteBackend>(value: &T, db: &mut DB, len: usize) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
 	T: IntoTree,
 	DB::Construct: CompatibleConstruct,
 {
-	let left = value.into_tree(db)?;
-	let right = U256::from(len).into_tree(db)?;
+	mix_in_length_at(value, db, len, MixPosition::default())
+}
 
-	(left, right).into_tree(db)
+/// Mix in length at the given position.
+pub fn mix_in_length_at<T, DB: WriteBackend>(value: &T, db: &mut DB, len: usize, position: MixPosition) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+	T: IntoTree,
+	DB::Construct: CompatibleConstruct,
+{
+	let len = U256::from(len);
+
+	match position {
+		MixPosition::Left => mix_in(&len, db, value),
+		MixPosition::Right => mix_in(value, db, &len),
+	}
+}
+
+/// Merkleize items from an iterator as a composite list. Only pending
+/// intermediate values are held, not the roots of all items.
+pub fn into_composite_list_tree_iter<I, DB: WriteBackend>(
+	items: I,
+	db: &mut DB,
+	max_len: Option<u64>,
+) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+	I: IntoIterator,
+	I::Item: IntoTree,
+	DB::Construct: CompatibleConstruct,
+{
+	let mut builder = VectorTreeBuilder::new(max_len);
+	for item in items {
+		let root = item.into_tree(db)?;
+		builder.push(db, root)?;
+	}
+	let len = builder.len() as usize;
+
+	mix_in_length(&builder.finish(db)?, db, len)
+}
+
+/// Merkleize a slice as a compact list, without building an owned
+/// collection. This is the packed counterpart of `[T]`'s `IntoTree`.
+pub fn compact_slice_root<T, DB: WriteBackend>(
+	slice: &[T],
+	db: &mut DB,
+	max_len: Option<u64>,
+) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+	for<'a> ElementalVariableVecRef<'a, T>: IntoCompactListTree,
+	DB::Construct: CompatibleConstruct,
+{
+	ElementalVariableVecRef(slice).into_compact_list_tree(db, max_len)
 }
 
 /// Decode length.
@@ -47,11 +138,143 @@ pub fn decode_with_length<T, DB: ReadBackend>(root: &<DB::Construct as Construct
 	T: FromTree,
 	DB::Construct: CompatibleConstruct,
 {
-	let (value, len) = <(T, U256)>::from_tree(root, db)?;
+	decode_with_length_at(root, db, MixPosition::default())
+}
+
+/// Decode a value with length mixed in at the given position.
+pub fn decode_with_length_at<T, DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB, position: MixPosition) -> Result<(T, usize), Error<DB::Error>> where
+	T: FromTree,
+	DB::Construct: CompatibleConstruct,
+{
+	let (value, len) = decode_with_length_u64_at(root, db, position)?;
+	Ok((value, narrow_length(len)?))
+}
+
+/// Decode a value with length mixed in, keeping the length as `u64`.
+pub fn decode_with_length_u64<T, DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<(T, u64), Error<DB::Error>> where
+	T: FromTree,
+	DB::Construct: CompatibleConstruct,
+{
+	decode_with_length_u64_at(root, db, MixPosition::default())
+}
 
-	if len > U256::from(usize::max_value()) {
+/// Decode a value with length mixed in at the given position, keeping the
+/// length as `u64`. Lengths not fitting `u64` are corrupted.
+pub fn decode_with_length_u64_at<T, DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB, position: MixPosition) -> Result<(T, u64), Error<DB::Error>> where
+	T: FromTree,
+	DB::Construct: CompatibleConstruct,
+{
+	let (value, len) = match position {
+		MixPosition::Left => {
+			let (len, value) = <(U256, T)>::from_tree(root, db)?;
+			(value, len)
+		},
+		MixPosition::Right => <(T, U256)>::from_tree(root, db)?,
+	};
+
+	if len > U256::from(u64::MAX) {
 		Err(Error::CorruptedDatabase)
 	} else {
-		Ok((value, len.as_usize()))
+		Ok((value, len.as_u64()))
+	}
+}
+
+/// Narrow a decoded length to `usize` for indexing. Returns
+/// `AccessOverflowed` if the platform cannot address it.
+pub fn narrow_length<E>(len: u64) -> Result<usize, Error<E>> {
+	if len > usize::MAX as u64 {
+		Err(Error::AccessOverflowed)
+	} else {
+		Ok(len as usize)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::str::FromStr;
+	use sha2::Sha256;
+	use bm::{InMemoryBackend, Construct};
+	use crate::DigestConstruct;
+
+	#[test]
+	fn test_zero_hashes() {
+		let hashes = zero_hashes::<Sha256>(3);
+		assert_eq!(hashes, vec![
+			H256::default(),
+			H256::from_str("f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b").unwrap(),
+			H256::from_str("db56114e00fdd4c1f85c892bf35ac9a89289aaecb1ebd0a96cde606a748b5d71").unwrap(),
+			H256::from_str("c78009fdf07fc56a11f122370658a353aaa542ed63e44c4bc15ff4cd105ab33c").unwrap(),
+		]);
+
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		for (depth, hash) in zero_hashes::<Sha256>(40).into_iter().enumerate() {
+			let empty = DigestConstruct::<Sha256>::empty_at(&mut db, depth).unwrap();
+			assert_eq!(empty.0, hash);
+		}
+	}
+
+	#[test]
+	fn test_mix_in() {
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		let value = 42u64;
+		let domain = H256::repeat_byte(0x07);
+
+		let root = mix_in(&value, &mut db, &domain).unwrap();
+		let (decoded_value, decoded_domain) = <(u64, H256)>::from_tree(&root, &mut db).unwrap();
+		assert_eq!(decoded_value, value);
+		assert_eq!(decoded_domain, domain);
+
+		assert_eq!(mix_in_type(&value, &mut db, 1).unwrap(), mix_in(&value, &mut db, &U256::from(1)).unwrap());
+		assert_eq!(mix_in_length(&value, &mut db, 5).unwrap(), mix_in(&value, &mut db, &U256::from(5)).unwrap());
+	}
+
+	#[test]
+	fn test_mix_position() {
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		let value = H256::repeat_byte(0x42);
+
+		let right = mix_in_length(&value, &mut db, 3).unwrap();
+		assert_eq!(right, (value, U256::from(3)).into_tree(&mut db).unwrap());
+		assert_eq!(mix_in_length_at(&value, &mut db, 3, MixPosition::Right).unwrap(), right);
+
+		let left = mix_in_length_at(&value, &mut db, 3, MixPosition::Left).unwrap();
+		assert_eq!(left, (U256::from(3), value).into_tree(&mut db).unwrap());
+		assert_ne!(left, right);
+
+		assert_eq!(decode_with_length::<H256, _>(&right, &mut db).unwrap(), (value, 3));
+		assert_eq!(decode_with_length_at::<H256, _>(&left, &mut db, MixPosition::Left).unwrap(), (value, 3));
+	}
+
+	#[test]
+	fn test_slice_root() {
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		let data = (0..37u64).collect::<Vec<_>>();
+		let slice = &data[3..20];
+
+		assert_eq!((&slice).into_tree(&mut db).unwrap(), slice.to_vec().into_tree(&mut db).unwrap());
+		assert_eq!(compact_slice_root(slice, &mut db, None).unwrap(), crate::Compact(slice.to_vec()).into_tree(&mut db).unwrap());
+		assert_eq!(
+			compact_slice_root(slice, &mut db, Some(64)).unwrap(),
+			crate::Compact(<crate::MaxVec::<u64, typenum::U64> as core::convert::TryFrom<_>>::try_from(slice.to_vec()).unwrap()).into_tree(&mut db).unwrap(),
+		);
+	}
+
+	#[test]
+	fn test_decode_length_u64() {
+		let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+		let value = H256::repeat_byte(0x42);
+
+		let large = (value, U256::from(u64::MAX)).into_tree(&mut db).unwrap();
+		assert_eq!(decode_with_length_u64::<H256, _>(&large, &mut db).unwrap(), (value, u64::MAX));
+
+		let overflowed = (value, U256::from(u64::MAX) + 1).into_tree(&mut db).unwrap();
+		assert_eq!(decode_with_length_u64::<H256, _>(&overflowed, &mut db), Err(Error::CorruptedDatabase));
+		assert_eq!(decode_with_length::<H256, _>(&overflowed, &mut db), Err(Error::CorruptedDatabase));
+
+		assert_eq!(narrow_length::<()>(3), Ok(3));
+		if (usize::MAX as u64) < u64::MAX {
+			assert_eq!(narrow_length::<()>(u64::MAX), Err(Error::AccessOverflowed));
+		}
 	}
 }
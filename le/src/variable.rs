@@ -2,10 +2,17 @@ use typenum::Unsigned;
 use bm::{Error, Construct, ReadBackend, WriteBackend};
 use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
+use core::convert::TryFrom;
 use alloc::vec::Vec;
+use alloc::collections::BTreeSet;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+use crate::utils::{into_composite_list_tree_iter, required_depth};
 use crate::{ElementalVariableVecRef, ElementalVariableVec,
 			IntoTree, IntoCompactListTree, IntoCompositeListTree,
-			FromTree, FromCompactListTree, FromCompositeListTree,
+			FromTree, FromCompactListTree, FromCompositeListTree, TreeShape,
 			Compact, CompactRef, CompatibleConstruct};
 
 /// Vec value with maximum length.
@@ -38,9 +45,15 @@ impl<T, ML> Default for MaxVec<T, ML> {
 	}
 }
 
-impl<T, ML> From<Vec<T>> for MaxVec<T, ML> {
-	fn from(vec: Vec<T>) -> Self {
-		Self(vec, PhantomData)
+impl<T, ML: Unsigned> TryFrom<Vec<T>> for MaxVec<T, ML> {
+	type Error = Vec<T>;
+
+	fn try_from(vec: Vec<T>) -> Result<Self, Vec<T>> {
+		if (vec.len() as u64) > ML::to_u64() {
+			Err(vec)
+		} else {
+			Ok(Self(vec, PhantomData))
+		}
 	}
 }
 
@@ -92,6 +105,10 @@ impl<T: parity_codec::Decode, N: Unsigned> parity_codec::Decode for MaxVec<T, N>
 	}
 }
 
+impl<T, ML: Unsigned> TreeShape for MaxVec<T, ML> {
+	fn depth() -> usize { required_depth(ML::to_u64()) + 1 }
+}
+
 impl<T, ML: Unsigned> IntoTree for MaxVec<T, ML> where
 	for<'b> ElementalVariableVecRef<'b, T>: IntoCompositeListTree,
 {
@@ -158,6 +175,9 @@ impl<T> IntoTree for [T] where
 	}
 }
 
+/// `Vec` is always merkleized as a composite list, including `Vec<bool>`.
+/// Wrap it in `Compact` to get the packed form, which for `bool` is the
+/// bitlist with its bit length mixed in.
 impl<T> IntoTree for Vec<T> where
 	for<'a> ElementalVariableVecRef<'a, T>: IntoCompositeListTree,
 {
@@ -177,3 +197,87 @@ impl<T> FromTree for Vec<T> where
 		ElementalVariableVec::from_composite_list_tree(root, db, None).map(|ret| ret.0)
 	}
 }
+
+impl<'a, T> IntoTree for CompactRef<'a, Vec<T>> where
+	for<'b> ElementalVariableVecRef<'b, T>: IntoCompactListTree,
+{
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		ElementalVariableVecRef(self.0).into_compact_list_tree(db, None)
+	}
+}
+
+impl<T> IntoTree for Compact<Vec<T>> where
+	for<'b> ElementalVariableVecRef<'b, T>: IntoCompactListTree,
+{
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		ElementalVariableVecRef(&self.0).into_compact_list_tree(db, None)
+	}
+}
+
+impl<T> FromTree for Compact<Vec<T>> where
+	ElementalVariableVec<T>: FromCompactListTree,
+{
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		ElementalVariableVec::from_compact_list_tree(root, db, None).map(|ret| Compact(ret.0))
+	}
+}
+
+/// `BTreeSet` is merkleized as a composite list of its items in ascending
+/// order. Decoding rejects unsorted or duplicate items.
+impl<T: IntoTree + Ord> IntoTree for BTreeSet<T> {
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		into_composite_list_tree_iter(self.iter(), db, None)
+	}
+}
+
+impl<T: FromTree + Ord> FromTree for BTreeSet<T> {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let items = ElementalVariableVec::<T>::from_composite_list_tree(root, db, None)?.0;
+
+		if items.windows(2).any(|w| w[0] >= w[1]) {
+			return Err(Error::CorruptedDatabase)
+		}
+
+		Ok(items.into_iter().collect())
+	}
+}
+
+/// `HashMap` is merkleized as a composite list of `(key, value)` tuples,
+/// sorted by key, so that the root does not depend on the map's iteration
+/// order. Decoding rejects unsorted or duplicate keys.
+#[cfg(feature = "std")]
+impl<K: IntoTree + Ord, V: IntoTree> IntoTree for HashMap<K, V> {
+	fn into_tree<DB: WriteBackend>(&self, db: &mut DB) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let mut entries = self.iter().collect::<Vec<_>>();
+		entries.sort_by(|a, b| a.0.cmp(b.0));
+
+		into_composite_list_tree_iter(entries, db, None)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K: FromTree + Ord + Hash, V: FromTree> FromTree for HashMap<K, V> {
+	fn from_tree<DB: ReadBackend>(root: &<DB::Construct as Construct>::Value, db: &mut DB) -> Result<Self, Error<DB::Error>> where
+		DB::Construct: CompatibleConstruct,
+	{
+		let entries = ElementalVariableVec::<(K, V)>::from_composite_list_tree(root, db, None)?.0;
+
+		if entries.windows(2).any(|w| w[0].0 >= w[1].0) {
+			return Err(Error::CorruptedDatabase)
+		}
+
+		Ok(entries.into_iter().collect())
+	}
+}
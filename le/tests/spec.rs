@@ -1,12 +1,16 @@
 use sha2::{Digest, Sha256};
-use primitive_types::H256;
+use primitive_types::{H160, H256, H512, U256};
 use std::fmt::Debug;
 use std::str::FromStr;
+use std::convert::TryFrom;
+use std::time::Duration;
+use std::collections::{BTreeSet, HashMap};
 use typenum::*;
 
-use bm::InMemoryBackend;
+use bm::{InMemoryBackend, Error};
 use generic_array::GenericArray;
-use bm_le::{IntoTree, FromTree, Compact, MaxVec, DigestConstruct};
+use vecarray::VecArray;
+use bm_le::{IntoTree, FromTree, Compact, BigEndian, Fixed, MaxVec, DigestConstruct, H128, H384, H768, tree_root};
 
 fn chunk(data: &[u8]) -> H256 {
 	let mut ret = [0; 32];
@@ -81,29 +85,241 @@ fn spec() {
 	}
 
 	// bitlist TTFTFTFF
-	t(Compact(MaxVec::<bool, U8>::from(vec![true, true, false, true, false, true, false, false])),
+	t(Compact(MaxVec::<bool, U8>::try_from(vec![true, true, false, true, false, true, false, false]).unwrap()),
 			   h(&chunk(&[0x2b])[..], &chunk(&[0x08])[..]));
 	// bitlist FTFT
-	t(Compact(MaxVec::<bool, U4>::from(vec![false, true, false, true])),
+	t(Compact(MaxVec::<bool, U4>::try_from(vec![false, true, false, true]).unwrap()),
 			   h(&chunk(&[0x0a])[..], &chunk(&[0x04])[..]));
 	// bitlist FTF
-	t(Compact(MaxVec::<bool, U3>::from(vec![false, true, false])),
+	t(Compact(MaxVec::<bool, U3>::try_from(vec![false, true, false]).unwrap()),
 			   h(&chunk(&[0x02])[..], &chunk(&[0x03])[..]));
 	// bitlist TFTFFFTTFT
-	t(Compact(MaxVec::<bool, U16>::from(vec![true, false, true, false, false, false, true, true, false, true])),
+	t(Compact(MaxVec::<bool, U16>::try_from(vec![true, false, true, false, false, false, true, true, false, true]).unwrap()),
 			   h(&chunk(&[0xc5, 0x02])[..], &chunk(&[0x0a])[..]));
 	// bitlist TFTFFFTTFTFFFFTT
-	t(Compact(MaxVec::<bool, U16>::from(vec![
+	t(Compact(MaxVec::<bool, U16>::try_from(vec![
 		true, false, true, false, false, false, true, true, false, true,
-		false, false, false, false, true, true])),
+		false, false, false, false, true, true]).unwrap()),
 	  h(&chunk(&[0xc5, 0xc2])[..], &chunk(&[0x10])[..]));
-	t(Compact(MaxVec::<bool, U4096>::from(vec![
+	t(Compact(MaxVec::<bool, U4096>::try_from(vec![
 		true, false, true, true, true, false, false, false
-	])), H256::from_str("f4de82badf841b3e8064de143959343ec7d4405e72d95bfc741748bb15721ff4").unwrap());
+	]).unwrap()), H256::from_str("f4de82badf841b3e8064de143959343ec7d4405e72d95bfc741748bb15721ff4").unwrap());
 
 	t(GenericArray::<H256, U0>::from_exact_iter(vec![]).unwrap(), H256::from_str("0000000000000000000000000000000000000000000000000000000000000000").unwrap());
 }
 
+#[test]
+fn address() {
+	let address = H160::from_str("5a0b54d5dc17e0aadc383d2db43b0a0d3e029c4c").unwrap();
+	t(address, chunk(&[0x5a, 0x0b, 0x54, 0xd5, 0xdc, 0x17, 0xe0, 0xaa, 0xdc, 0x38,
+					   0x3d, 0x2d, 0xb4, 0x3b, 0x0a, 0x0d, 0x3e, 0x02, 0x9c, 0x4c]));
+}
+
+#[test]
+fn fixed_hashes() {
+	let bytes = (0..96u8).collect::<Vec<_>>();
+
+	t(H128::from_slice(&bytes[..16]), chunk(&bytes[..16]));
+	t(H384::from_slice(&bytes[..48]), h(&bytes[..32], &chunk(&bytes[32..48])[..]));
+	t(H768::from_slice(&bytes[..96]), h(
+		&h(&bytes[..32], &bytes[32..64])[..],
+		&h(&bytes[64..96], &[0u8; 32])[..],
+	));
+}
+
+#[test]
+fn h512() {
+	let bytes = (0..64u8).collect::<Vec<_>>();
+	t(H512::from_slice(&bytes), h(&bytes[..32], &bytes[32..]));
+}
+
+#[test]
+fn result() {
+	t(Ok::<u64, u32>(3), h(&chunk(&3u64.to_le_bytes())[..], &chunk(&[0x00])[..]));
+	t(Err::<u64, u32>(5), h(&chunk(&5u32.to_le_bytes())[..], &chunk(&[0x01])[..]));
+	t(Ok::<Option<u8>, ()>(None), h(&h(&[0u8; 32], &[0u8; 32])[..], &chunk(&[0x00])[..]));
+
+	let hash = H256::repeat_byte(0xab);
+	t(Ok::<u64, H256>(7), h(&chunk(&7u64.to_le_bytes())[..], &chunk(&[0x00])[..]));
+	t(Err::<u64, H256>(hash), h(&hash[..], &chunk(&[0x01])[..]));
+
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let corrupted = (7u64, 2u8).into_tree(&mut db).unwrap();
+	assert_eq!(Result::<u64, H256>::from_tree(&corrupted, &mut db), Err(Error::CorruptedDatabase));
+}
+
+#[test]
+fn signed() {
+	t(-1i8, chunk(&[0xff]));
+	t(-2i32, chunk(&[0xfe, 0xff, 0xff, 0xff]));
+	t(i64::MIN, chunk(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80]));
+	t(-3i128, chunk(&(-3i128).to_le_bytes()));
+
+	let values = vec![1i32, -1, i32::MIN, i32::MAX, 0, -42, 7, -8, 9];
+	let mut packed = Vec::new();
+	for value in &values {
+		packed.extend_from_slice(&value.to_le_bytes());
+	}
+	t(Compact(MaxVec::<i32, U16>::try_from(values.clone()).unwrap()), h(
+		&h(&packed[..32], &chunk(&packed[32..])[..])[..],
+		&chunk(&9u32.to_le_bytes())[..],
+	));
+	t(values, tree_root::<Sha256, _>(&vec![1i32, -1, i32::MIN, i32::MAX, 0, -42, 7, -8, 9]));
+	t(Compact(GenericArray::<i16, U3>::from([-1, 2, -3])), chunk(&[0xff, 0xff, 0x02, 0x00, 0xfd, 0xff]));
+}
+
+#[test]
+fn large_tuples() {
+	let c = |i: u8| chunk(&[i]);
+	let leaves = (0..16u8).map(|i| if i < 10 { c(i) } else { H256::default() }).collect::<Vec<_>>();
+	let mut layer = leaves;
+	while layer.len() > 1 {
+		layer = layer.chunks(2).map(|pair| h(&pair[0][..], &pair[1][..])).collect();
+	}
+
+	t((0u8, 1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8, 9u8), layer[0]);
+	t((0u8, 1u16, 2u32, 3u64, 4u128, 5u8, 6u16, 7u32, 8u64, 9u128, true, H256::repeat_byte(1)),
+	  tree_root::<Sha256, _>(&(0u8, 1u16, 2u32, 3u64, 4u128, 5u8, 6u16, 7u32, 8u64, 9u128, true, H256::repeat_byte(1))));
+}
+
+#[test]
+fn heterogeneous_tuple() {
+	let mut leaves = [1u8, 2, 3, 4, 5, 1].iter().map(|i| chunk(&[*i])).collect::<Vec<_>>();
+	leaves.push(H256::repeat_byte(7));
+	leaves.extend([8u8, 9, 10].iter().map(|i| chunk(&[*i])));
+	leaves.resize(16, H256::default());
+	let mut layer = leaves;
+	while layer.len() > 1 {
+		layer = layer.chunks(2).map(|pair| h(&pair[0][..], &pair[1][..])).collect();
+	}
+
+	t((1u8, 2u16, 3u32, 4u64, 5u128, true, H256::repeat_byte(7), 8u64, U256::from(9), 10u16), layer[0]);
+}
+
+#[test]
+fn hlist() {
+	let tail = h(&chunk(&3u32.to_le_bytes())[..], &H256::default()[..]);
+	let middle = h(&chunk(&2u16.to_le_bytes())[..], &tail[..]);
+	let root = h(&chunk(&[1u8])[..], &middle[..]);
+
+	t((1u8, (2u16, (3u32, ()))), root);
+}
+
+#[test]
+fn empty_vectors() {
+	let zero = H256::default();
+
+	t(VecArray::<u64, U0>::default(), zero);
+	t(Compact(VecArray::<u64, U0>::default()), zero);
+	t(Compact(GenericArray::<u8, U0>::default()), zero);
+	t(VecArray::<(u64, u64), U0>::default(), zero);
+	t(GenericArray::<(u8, u8), U0>::default(), zero);
+
+	t(Vec::<u64>::new(), h(&zero[..], &zero[..]));
+	t(Vec::<(u64, u64)>::new(), h(&zero[..], &zero[..]));
+	t(Compact(MaxVec::<u64, U8>::default()), h(&h(&zero[..], &zero[..])[..], &zero[..]));
+	t(MaxVec::<(u64, u64), U4>::default(), h(&h(&h(&zero[..], &zero[..])[..], &h(&zero[..], &zero[..])[..])[..], &zero[..]));
+}
+
+#[test]
+fn big_endian() {
+	let value = U256::from(0x0102u64);
+	let mut be = [0u8; 32];
+	be[30] = 0x01;
+	be[31] = 0x02;
+	t(value, chunk(&[0x02, 0x01]));
+	t(BigEndian(value), H256::from(be));
+	assert_ne!(tree_root::<Sha256, _>(&value), tree_root::<Sha256, _>(&BigEndian(value)));
+
+	let value = primitive_types::U512::from(0x0102u64);
+	let mut be = [0u8; 32];
+	be[30] = 0x01;
+	be[31] = 0x02;
+	t(value, h(&chunk(&[0x02, 0x01])[..], &[0u8; 32]));
+	t(BigEndian(value), h(&[0u8; 32], &be));
+}
+
+#[test]
+fn fixed_point() {
+	t(Fixed::<4>(-1_234_567), chunk(&(-1_234_567i128).to_le_bytes()));
+	t(Fixed::<0>(i128::MIN), chunk(&i128::MIN.to_le_bytes()));
+	assert_eq!(tree_root::<Sha256, _>(&Fixed::<2>(-5)), tree_root::<Sha256, _>(&Fixed::<8>(-5)));
+}
+
+#[test]
+fn duration() {
+	t(Duration::new(1_565_000_000, 250_000_000),
+	  h(&chunk(&1_565_000_000u64.to_le_bytes())[..], &chunk(&250_000_000u32.to_le_bytes())[..]));
+}
+
+#[test]
+fn max_vec_bound() {
+	assert_eq!(MaxVec::<u64, U2>::try_from(vec![1, 2]).unwrap().0, vec![1, 2]);
+	assert_eq!(MaxVec::<u64, U2>::try_from(vec![1, 2, 3]), Err(vec![1, 2, 3]));
+
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let mut value = MaxVec::<u64, U2>::try_from(vec![1, 2]).unwrap();
+	value.push(3);
+	assert_eq!(value.into_tree(&mut db), Err(Error::InvalidParameter));
+	assert_eq!(Compact(value).into_tree(&mut db), Err(Error::InvalidParameter));
+}
+
+#[test]
+fn vec_bool_bitlist() {
+	let bits = vec![true, false, true, false, false, false, true, true, false, true];
+	let bitlist = h(&chunk(&[0xc5, 0x02])[..], &chunk(&[0x0a])[..]);
+
+	t(Compact(bits.clone()), bitlist);
+
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	assert_eq!(Compact(bits.clone()).into_tree(&mut db).unwrap(),
+			   Compact(MaxVec::<bool, U16>::try_from(bits.clone()).unwrap()).into_tree(&mut db).unwrap());
+	assert_ne!(H256::from_slice(bits.into_tree(&mut db).unwrap().as_ref()), bitlist);
+}
+
+#[cfg(feature = "parity-codec")]
+#[test]
+fn encode_tree_root() {
+	let mut bytes = 1u32.to_le_bytes().to_vec();
+	bytes.extend_from_slice(&2u64.to_le_bytes());
+
+	assert_eq!(bm_le::encode_tree_root::<Sha256, _>(&(1u32, 2u64)), h(&chunk(&bytes)[..], &chunk(&[12])[..]));
+	assert_eq!(bm_le::encode_tree_root::<Sha256, _>(&(1u32, 2u64)), tree_root::<Sha256, _>(&Compact(bytes)));
+}
+
+#[test]
+fn btree_set() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let set = vec![5u64, 1, 3].into_iter().collect::<BTreeSet<_>>();
+
+	let root = set.into_tree(&mut db).unwrap();
+	assert_eq!(root, vec![1u64, 3, 5].into_tree(&mut db).unwrap());
+	assert_eq!(BTreeSet::<u64>::from_tree(&root, &mut db).unwrap(), set);
+
+	let duplicated = vec![1u64, 1].into_tree(&mut db).unwrap();
+	assert!(BTreeSet::<u64>::from_tree(&duplicated, &mut db).is_err());
+}
+
+#[test]
+fn hash_map() {
+	let mut db = InMemoryBackend::<DigestConstruct<Sha256>>::default();
+	let expected = vec![(1u64, 10u32), (2, 20), (3, 30), (4, 40)].into_tree(&mut db).unwrap();
+
+	let mut forward = HashMap::new();
+	let mut backward = HashMap::new();
+	for i in 1..5u64 {
+		forward.insert(i, i as u32 * 10);
+		backward.insert(5 - i, (5 - i) as u32 * 10);
+	}
+
+	assert_eq!(forward.into_tree(&mut db).unwrap(), expected);
+	assert_eq!(backward.into_tree(&mut db).unwrap(), expected);
+	assert_eq!(HashMap::<u64, u32>::from_tree(&expected, &mut db).unwrap(), forward);
+
+	let unsorted = vec![(2u64, 20u32), (1, 10)].into_tree(&mut db).unwrap();
+	assert!(HashMap::<u64, u32>::from_tree(&unsorted, &mut db).is_err());
+}
+
 // test_data = [
 //	   ("long bitlist", Bitlist[512](1),
 //		"03", h(h(chunk("01"), chunk("")), chunk("01"))),
@@ -0,0 +1,93 @@
+//! Serde adapter for `CompactValue` of a single-path proof. In
+//! human-readable formats, the value is flattened into a leaf plus its
+//! siblings ordered from the leaf up to the root, each with the side the
+//! sibling is on. Other formats keep the nested enum. The compact value
+//! does not record which bottom value is the leaf, so when both are single
+//! values the left one is taken as the leaf. This round-trips to the same
+//! compact value.
+//!
+//! Use it with `#[serde(with = "bm::compact_flat")]`. Serializing a compact
+//! value that is not a single path into a human-readable format errors.
+
+use crate::CompactValue;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use serde::{Serialize, Deserialize, Serializer, Deserializer, ser::Error};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+	Left,
+	Right,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Sibling<V> {
+	direction: Direction,
+	value: V,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Flat<V> {
+	leaf: V,
+	siblings: Vec<Sibling<V>>,
+}
+
+fn flatten<V: Clone>(compact: &CompactValue<V>) -> Option<Flat<V>> {
+	match compact {
+		CompactValue::Single(leaf) => Some(Flat { leaf: leaf.clone(), siblings: Vec::new() }),
+		CompactValue::Combined(boxed) => {
+			let (mut flat, sibling) = match boxed.as_ref() {
+				(path, CompactValue::Single(value)) => (
+					flatten(path)?,
+					Sibling { direction: Direction::Right, value: value.clone() },
+				),
+				(CompactValue::Single(value), path) => (
+					flatten(path)?,
+					Sibling { direction: Direction::Left, value: value.clone() },
+				),
+				_ => return None,
+			};
+			flat.siblings.push(sibling);
+			Some(flat)
+		},
+	}
+}
+
+fn unflatten<V>(flat: Flat<V>) -> CompactValue<V> {
+	let mut current = CompactValue::Single(flat.leaf);
+	for sibling in flat.siblings {
+		let sibling_value = CompactValue::Single(sibling.value);
+		current = match sibling.direction {
+			Direction::Left => CompactValue::Combined(Box::new((sibling_value, current))),
+			Direction::Right => CompactValue::Combined(Box::new((current, sibling_value))),
+		};
+	}
+	current
+}
+
+/// Serialize a compact value, flattened if the format is human-readable.
+pub fn serialize<V, S>(value: &CompactValue<V>, serializer: S) -> Result<S::Ok, S::Error> where
+	V: Serialize + Clone,
+	S: Serializer,
+{
+	if serializer.is_human_readable() {
+		flatten(value)
+			.ok_or_else(|| S::Error::custom("compact value is not a single-path proof"))?
+			.serialize(serializer)
+	} else {
+		value.serialize(serializer)
+	}
+}
+
+/// Deserialize a compact value, flattened if the format is human-readable.
+pub fn deserialize<'de, V, D>(deserializer: D) -> Result<CompactValue<V>, D::Error> where
+	V: Deserialize<'de>,
+	D: Deserializer<'de>,
+{
+	if deserializer.is_human_readable() {
+		Flat::deserialize(deserializer).map(unflatten)
+	} else {
+		CompactValue::deserialize(deserializer)
+	}
+}
@@ -35,7 +35,7 @@ impl IndexRoute {
 }
 
 /// Raw merkle index.
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Index(usize);
 
 impl Index {
@@ -65,16 +65,16 @@ impl Index {
 
 	/// Whether this index has given descendant.
 	pub fn has_descendant(&self, other: &Index) -> bool {
-		match other.parent() {
-			Some(parent) => {
-				if parent == *self {
-					true
-				} else {
-					self.has_descendant(&parent)
-				}
-			}
-			None => false,
-		}
+		self.is_ancestor_of(other)
+	}
+
+	/// Whether this index lies strictly on the route from root to the
+	/// other index.
+	pub fn is_ancestor_of(&self, other: &Index) -> bool {
+		let self_depth = self.route_len();
+		let other_depth = other.route_len();
+
+		other_depth > self_depth && other.0 >> (other_depth - self_depth) == self.0
 	}
 
 	/// From one-based index.
@@ -96,6 +96,12 @@ impl Index {
 		Self((1 << depth) + index)
 	}
 
+	/// Number of selections from the root to the current index, which is
+	/// its depth, without allocating the route.
+	pub fn route_len(&self) -> usize {
+		(usize::BITS - 1 - self.0.leading_zeros()) as usize
+	}
+
 	/// Get selections from current index.
 	pub fn route(&self) -> IndexRoute {
 		let mut value = self.0;
@@ -124,7 +130,9 @@ impl Index {
 		}
 	}
 
-	/// Get sub from current index.
+	/// Get sub from current index, grafting the route of `sub` beneath the
+	/// current index. This is generalized index multiplication: with `d`
+	/// the depth of `sub`, the result is `self * 2^d + (sub - 2^d)`.
 	pub fn sub(&self, sub: Index) -> Index {
 		let route = sub.route();
 
@@ -154,4 +162,43 @@ mod tests {
 		assert!(Index::root().left().has_descendant(&Index::root().left().right().left().right().right()));
 		assert!(!Index::root().left().has_descendant(&Index::root().right().right().left().right().right()));
 	}
+
+	#[test]
+	fn test_is_ancestor_of() {
+		assert!(Index::root().left().is_ancestor_of(&Index::root().left().right().left().right().right()));
+		assert!(!Index::root().left().is_ancestor_of(&Index::root().right().right().left().right().right()));
+		assert!(Index::root().is_ancestor_of(&Index::from_one(13).unwrap()));
+		assert!(Index::from_one(3).unwrap().is_ancestor_of(&Index::from_one(13).unwrap()));
+		assert!(!Index::from_one(13).unwrap().is_ancestor_of(&Index::from_one(3).unwrap()));
+		assert!(!Index::from_one(13).unwrap().is_ancestor_of(&Index::from_one(13).unwrap()));
+		assert!(!Index::root().is_ancestor_of(&Index::root()));
+		assert!(!Index::from_one(2).unwrap().is_ancestor_of(&Index::from_one(3).unwrap()));
+		assert!(Index::from_one(2).unwrap().is_ancestor_of(&Index::from_depth(0, usize::BITS as usize - 2)));
+		assert!(Index::from_one(3).unwrap().is_ancestor_of(&Index::from_depth(usize::MAX >> 2, usize::BITS as usize - 2)));
+	}
+
+	#[test]
+	fn test_route_len() {
+		for value in (1..2048).chain((0..usize::BITS).map(|shift| 1usize << shift)).chain(Some(usize::MAX)) {
+			let index = Index::from_one(value).unwrap();
+			let expected = match index.route() {
+				IndexRoute::Root => 0,
+				IndexRoute::Select(selections) => selections.len(),
+			};
+			assert_eq!(index.route_len(), expected);
+		}
+	}
+
+	#[test]
+	fn test_sub() {
+		let parent = Index::from_one(5).unwrap();
+		assert_eq!(parent.sub(Index::root()), parent);
+		assert_eq!(parent.sub(Index::from_one(3).unwrap()), Index::from_one(11).unwrap());
+		assert_eq!(parent.sub(Index::from_one(6).unwrap()), Index::from_one(22).unwrap());
+		assert_eq!(Index::root().sub(Index::from_one(13).unwrap()), Index::from_one(13).unwrap());
+		assert_eq!(
+			Index::from_one(2).unwrap().sub(Index::from_one(3).unwrap()).sub(Index::from_one(2).unwrap()),
+			Index::from_one(2).unwrap().sub(Index::from_one(6).unwrap()),
+		);
+	}
 }
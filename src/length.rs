@@ -41,6 +41,18 @@ impl<R: RootStatus, C: Construct, S> LengthMixed<R, C, S> where
 		Ok(self.raw.root())
 	}
 
+	/// Collapse the mixed-length tree into its root hash, keeping all its
+	/// nodes in the backend. Fails with `CorruptedDatabase` if the length
+	/// or the item root cannot be read back from the backend.
+	pub fn persist_root<DB: ReadBackend<Construct=C> + ?Sized>(
+		self,
+		db: &mut DB
+	) -> Result<C::Value, Error<DB::Error>> {
+		self.raw.get(db, LEN_INDEX)?.ok_or(Error::CorruptedDatabase)?;
+		self.raw.get(db, ITEM_ROOT_INDEX)?.ok_or(Error::CorruptedDatabase)?;
+		Ok(self.raw.root())
+	}
+
 	/// Call with the inner sequence.
 	pub fn with<DB: Backend<Construct=C> + ?Sized, RT, F>(
 		&self,
@@ -71,6 +83,32 @@ impl<R: RootStatus, C: Construct, S> LengthMixed<R, C, S> where
 	}
 }
 
+impl<R: RootStatus, C: Construct, S> LengthMixed<R, C, S> where
+	S: Sequence<Construct=C, RootStatus=Dangling> + Leak,
+	C::Value: From<usize> + Into<usize> + PartialEq,
+{
+	/// Create the mixed-length tree from leaked metadata, checking that
+	/// the root resolves to the length and the item root in the metadata.
+	/// Fails with `CorruptedDatabase` otherwise.
+	pub fn try_from_leaked<DB: ReadBackend<Construct=C> + ?Sized>(
+		metadata: <Self as Leak>::Metadata,
+		db: &mut DB
+	) -> Result<Self, Error<DB::Error>> {
+		let ret = Self::from_leaked(metadata);
+		let len: usize = ret.raw.get(db, LEN_INDEX)?
+			.ok_or(Error::CorruptedDatabase)?
+			.into();
+		let item_root = ret.raw.get(db, ITEM_ROOT_INDEX)?
+			.ok_or(Error::CorruptedDatabase)?;
+
+		if len != ret.inner.len() || item_root != ret.inner.root() {
+			return Err(Error::CorruptedDatabase)
+		}
+
+		Ok(ret)
+	}
+}
+
 impl<C: Construct, S> LengthMixed<Owned, C, S> where
 	S: Sequence<Construct=C, RootStatus=Dangling> + Leak,
 	C::Value: From<usize> + Into<usize>,
@@ -14,11 +14,15 @@ mod list;
 mod packed;
 mod length;
 mod proving;
+mod scoped;
 
 pub mod utils;
+#[cfg(feature = "serde")]
+pub mod compact_flat;
 
-pub use crate::traits::{Backend, ReadBackend, WriteBackend, Construct, Dangling, Owned, RootStatus, Error, Sequence, Tree, Leak, DynBackend};
-pub use crate::memory::{EmptyStatus, UnitEmpty, InheritedEmpty, UnitDigestConstruct, InheritedDigestConstruct, InMemoryBackend, InMemoryBackendError, NoopBackend, NoopBackendError};
+pub use crate::traits::{Backend, ReadBackend, WriteBackend, Construct, Dangling, Owned, RootStatus, Error, Sequence, Tree, Leak, DynBackend, BatchItem};
+pub use crate::memory::{EmptyStatus, UnitEmpty, InheritedEmpty, UnitDigestConstruct, InheritedDigestConstruct, DomainSeparatedConstruct, PrehashConstruct, InMemoryBackend, InMemoryBackendError,
+					   InMemoryEntry, InMemoryOccupiedEntry, InMemoryVacantEntry, InMemoryNode, InterningBackend, NoopBackend, NoopBackendError, EvictCallback};
 pub use crate::raw::{Raw, OwnedRaw, DanglingRaw};
 pub use crate::index::{Index, IndexSelection, IndexRoute};
 pub use crate::vector::{Vector, OwnedVector, DanglingVector};
@@ -26,4 +30,6 @@ pub use crate::list::{List, OwnedList, DanglingList};
 pub use crate::packed::{PackedVector, OwnedPackedVector, DanglingPackedVector,
 						PackedList, OwnedPackedList, DanglingPackedList};
 pub use crate::length::LengthMixed;
-pub use crate::proving::{ProvingBackend, ProvingState, Proofs, CompactValue};
+pub use crate::scoped::Scoped;
+pub use crate::proving::{ProvingBackend, ProvingState, Proofs, CompactValue, CompactBackend, verify_proof,
+						 verify_proof_fixed, QueryProver, MultiProof, ElementProof};
@@ -2,6 +2,11 @@ use crate::traits::{ReadBackend, WriteBackend, Construct, RootStatus, Dangling,
 use crate::vector::Vector;
 use crate::raw::Raw;
 use crate::length::LengthMixed;
+use crate::index::Index;
+use crate::proving::{ProvingBackend, Proofs, ElementProof};
+use core::hash::Hash;
+use core::cmp::Ordering;
+use alloc::vec::Vec;
 
 /// `List` with owned root.
 pub type OwnedList<C> = List<Owned, C>;
@@ -20,6 +25,60 @@ impl<R: RootStatus, C: Construct> List<R, C> where
 		self.0.with(db, |tuple, db| tuple.get(db, index))
 	}
 
+	/// Get value at index, returning `None` if the index is out of range.
+	pub fn try_get<DB: ReadBackend<Construct=C> + ?Sized>(&self, db: &mut DB, index: usize) -> Result<Option<C::Value>, Error<DB::Error>> {
+		self.0.with(db, |tuple, db| {
+			if index >= tuple.len() {
+				Ok(None)
+			} else {
+				tuple.get(db, index).map(Some)
+			}
+		})
+	}
+
+	/// Read all values of the list, in index order. When the value type
+	/// implements serde's `Serialize`, the result can be serialized to dump
+	/// the logical contents rather than the tree nodes.
+	pub fn to_values<DB: ReadBackend<Construct=C> + ?Sized>(&self, db: &mut DB) -> Result<Vec<C::Value>, Error<DB::Error>> {
+		self.0.with(db, |tuple, db| tuple.to_values(db))
+	}
+
+	/// Binary search a sorted list for the given value. Returns `Ok(index)`
+	/// if found, or `Err(index)` of the insertion point otherwise. Each
+	/// probe does one `get`.
+	pub fn binary_search<DB: ReadBackend<Construct=C> + ?Sized>(&self, db: &mut DB, value: &C::Value) -> Result<Result<usize, usize>, Error<DB::Error>> where
+		C::Value: Ord,
+	{
+		self.0.with(db, |tuple, db| {
+			let mut low = 0;
+			let mut high = tuple.len();
+
+			while low < high {
+				let mid = low + (high - low) / 2;
+				match tuple.get(db, mid)?.cmp(value) {
+					Ordering::Less => low = mid + 1,
+					Ordering::Greater => high = mid,
+					Ordering::Equal => return Ok(Ok(mid)),
+				}
+			}
+
+			Ok(Err(low))
+		})
+	}
+
+	/// Get the value at index together with a compact proof of it against
+	/// the list root. The proof includes the length node.
+	pub fn prove_element<DB: ReadBackend<Construct=C> + ?Sized>(&self, db: &mut DB, index: usize) -> Result<ElementProof<C::Value>, Error<DB::Error>> where
+		C::Value: Eq + Hash + Ord,
+	{
+		let root = self.root();
+		let mut proving = ProvingBackend::new(db);
+		Raw::<Dangling, C>::from_leaked(root.clone()).get(&mut proving, Index::root().right())?;
+		let value = self.get(&mut proving, index)?;
+
+		Ok((value, Proofs::from(proving).into_compact(root)))
+	}
+
 	/// Set value at index.
 	pub fn set<DB: WriteBackend<Construct=C> + ?Sized>(&mut self, db: &mut DB, index: usize, value: C::Value) -> Result<(), Error<DB::Error>> {
 		self.0.with_mut(db, |tuple, db| tuple.set(db, index, value))
@@ -35,11 +94,25 @@ impl<R: RootStatus, C: Construct> List<R, C> where
 		self.0.with_mut(db, |tuple, db| tuple.pop(db))
 	}
 
+	/// Remove up to `count` trailing values from the vector, returned in
+	/// index order. The length node is only updated once.
+	pub fn drain_tail<DB: WriteBackend<Construct=C> + ?Sized>(&mut self, db: &mut DB, count: usize) -> Result<Vec<C::Value>, Error<DB::Error>> {
+		self.0.with_mut(db, |tuple, db| tuple.pop_many(db, count))
+	}
+
 	/// Deconstruct the vector into one single hash value, and leak only the hash value.
 	pub fn deconstruct<DB: ReadBackend<Construct=C> + ?Sized>(self, db: &mut DB) -> Result<C::Value, Error<DB::Error>> {
 		self.0.deconstruct(db)
 	}
 
+	/// Collapse the list into one single hash value, keeping its nodes in
+	/// the backend so that it can later be taken back by `reconstruct`.
+	/// Unlike `drop`, no node is released. The backend must hold the
+	/// list's nodes; otherwise `CorruptedDatabase` is returned.
+	pub fn persist_root<DB: ReadBackend<Construct=C> + ?Sized>(self, db: &mut DB) -> Result<C::Value, Error<DB::Error>> {
+		self.0.persist_root(db)
+	}
+
 	/// Reconstruct the vector from a single hash value.
 	pub fn reconstruct<DB: WriteBackend<Construct=C> + ?Sized>(root: C::Value, db: &mut DB, max_len: Option<u64>) -> Result<Self, Error<DB::Error>> {
 		Ok(Self(LengthMixed::reconstruct(root, db, |tuple_raw, _db, len| {
@@ -47,6 +120,35 @@ impl<R: RootStatus, C: Construct> List<R, C> where
 		})?))
 	}
 
+	/// Create a list from leaked metadata, checking that its root resolves
+	/// to the length and the item root in the metadata. Fails with
+	/// `CorruptedDatabase` otherwise.
+	pub fn try_from_leaked<DB: ReadBackend<Construct=C> + ?Sized>(
+		metadata: <Self as Leak>::Metadata,
+		db: &mut DB
+	) -> Result<Self, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		Ok(Self(LengthMixed::try_from_leaked(metadata, db)?))
+	}
+
+	/// Create a list from leaked metadata, checking both the length node
+	/// as `try_from_leaked` does, and the item vector as
+	/// `Vector::checked_from_leaked` does.
+	pub fn checked_from_leaked<DB: ReadBackend<Construct=C> + ?Sized>(
+		metadata: <Self as Leak>::Metadata,
+		db: &mut DB
+	) -> Result<Self, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		let ret = Self::try_from_leaked(metadata, db)?;
+		ret.0.with(db, |tuple, db| {
+			Vector::<Dangling, C>::checked_from_leaked(tuple.metadata(), db).map(|_| ())
+		})?;
+
+		Ok(ret)
+	}
+
 	/// Create a list from raw merkle tree.
 	pub fn from_raw(raw: Raw<R, C>, len: usize, max_len: Option<u64>) -> Self {
 		Self::from_leaked((raw.metadata(), (raw.metadata(), len, max_len)))
@@ -94,6 +196,20 @@ impl<R: RootStatus, C: Construct> Leak for List<R, C> where
 	}
 }
 
+/// Lists are equal when their roots and lengths are equal. This is a hash
+/// equality, and does not compare backend contents.
+impl<R1: RootStatus, R2: RootStatus, C: Construct> PartialEq<List<R2, C>> for List<R1, C> where
+	C::Value: From<usize> + Into<usize> + PartialEq,
+{
+	fn eq(&self, other: &List<R2, C>) -> bool {
+		self.root() == other.root() && self.len() == other.len()
+	}
+}
+
+impl<R: RootStatus, C: Construct> Eq for List<R, C> where
+	C::Value: From<usize> + Into<usize> + Eq,
+{ }
+
 impl<C: Construct> List<Owned, C> where
 	C::Value: From<usize> + Into<usize>
 {
@@ -104,6 +220,18 @@ impl<C: Construct> List<Owned, C> where
 	) -> Result<Self, Error<DB::Error>> {
 		Ok(Self(LengthMixed::create(db, |db| Vector::<Owned, _>::create(db, 0, max_len))?))
 	}
+
+	/// Create a new list from the given values. This is the inverse of
+	/// `to_values`.
+	pub fn from_slice<DB: WriteBackend<Construct=C> + ?Sized>(
+		db: &mut DB,
+		values: &[C::Value],
+		max_len: Option<u64>
+	) -> Result<Self, Error<DB::Error>> {
+		let mut list = Self::create(db, max_len)?;
+		list.0.with_mut(db, |tuple, db| tuple.extend_from_slice(db, values))?;
+		Ok(list)
+	}
 }
 
 impl<R: RootStatus, C: Construct> Raw<R, C> {
@@ -175,6 +303,29 @@ mod tests {
 		assert_eq!(vec.len(), 0);
 	}
 
+	#[test]
+	fn test_drain_tail() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = List::create(&mut db, None).unwrap();
+		for i in 0..100 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+
+		let mut truncated_db = InheritedInMemory::default();
+		let mut truncated = List::create(&mut truncated_db, None).unwrap();
+		for i in 0..70 {
+			truncated.push(&mut truncated_db, i.into()).unwrap();
+		}
+
+		let values = vec.drain_tail(&mut db, 30).unwrap();
+		assert_eq!(values, (70..100).map(Into::into).collect::<Vec<ListValue>>());
+		assert_eq!(vec.len(), 70);
+		assert_eq!(vec.root(), truncated.root());
+
+		assert_eq!(vec.drain_tail(&mut db, 100).unwrap().len(), 70);
+		assert_eq!(vec.root(), List::create(&mut truncated_db, None).unwrap().root());
+	}
+
 	#[test]
 	fn test_push_pop_unit() {
 		let mut db = UnitInMemory::default();
@@ -197,6 +348,39 @@ mod tests {
 		assert_eq!(vec.len(), 0);
 	}
 
+	#[test]
+	fn test_empty_modes() {
+		let mut inherited_db = InheritedInMemory::new_with_inherited_empty();
+		let mut unit_db = UnitInMemory::new_with_unit_empty();
+		let mut inherited = OwnedList::create(&mut inherited_db, None).unwrap();
+		let mut unit = OwnedList::create(&mut unit_db, None).unwrap();
+
+		for i in 0..5 {
+			inherited.push(&mut inherited_db, i.into()).unwrap();
+			unit.push(&mut unit_db, i.into()).unwrap();
+		}
+		assert_ne!(inherited.root(), unit.root());
+		for i in 0..5 {
+			assert_eq!(inherited.get(&mut inherited_db, i).unwrap(), unit.get(&mut unit_db, i).unwrap());
+		}
+	}
+
+	#[test]
+	fn test_is_empty() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+
+		assert!(vec.is_empty());
+		vec.push(&mut db, 1.into()).unwrap();
+		assert!(!vec.is_empty());
+		vec.pop(&mut db).unwrap();
+		assert!(vec.is_empty());
+
+		// The length is mixed in, so even an empty list has a non-empty
+		// root.
+		assert!(!vec.is_empty_root());
+	}
+
 	#[test]
 	fn test_set() {
 		let mut db = InheritedInMemory::default();
@@ -215,6 +399,271 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_try_get() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+
+		for i in 0..10 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+
+		assert_eq!(vec.try_get(&mut db, 9).unwrap(), Some(9.into()));
+		assert_eq!(vec.try_get(&mut db, 10).unwrap(), None);
+		assert_eq!(vec.try_get(&mut db, 1000).unwrap(), None);
+		assert_eq!(vec.get(&mut db, 10), Err(Error::AccessOverflowed));
+	}
+
+	#[test]
+	fn test_to_values_from_slice() {
+		let mut db = InheritedInMemory::default();
+		let mut list = OwnedList::create(&mut db, None).unwrap();
+		assert_eq!(list.to_values(&mut db).unwrap(), Vec::new());
+
+		for i in 0..13 {
+			list.push(&mut db, i.into()).unwrap();
+		}
+
+		let values = list.to_values(&mut db).unwrap();
+		assert_eq!(values, (0..13).map(ListValue::from).collect::<Vec<_>>());
+
+		let other = OwnedList::from_slice(&mut db, &values, None).unwrap();
+		assert_eq!(other.root(), list.root());
+		assert_eq!(other.to_values(&mut db).unwrap(), values);
+	}
+
+	#[test]
+	fn test_binary_search() {
+		let mut db = InheritedInMemory::default();
+		let mut list = OwnedList::create(&mut db, None).unwrap();
+		assert_eq!(list.binary_search(&mut db, &0.into()).unwrap(), Err(0));
+
+		for i in 0..64 {
+			list.push(&mut db, (i * 2).into()).unwrap();
+		}
+
+		for i in 0..64 {
+			assert_eq!(list.binary_search(&mut db, &(i * 2).into()).unwrap(), Ok(i));
+			assert_eq!(list.binary_search(&mut db, &(i * 2 + 1).into()).unwrap(), Err(i + 1));
+		}
+	}
+
+	#[test]
+	fn test_eq() {
+		let mut db = InheritedInMemory::default();
+		let mut list1 = OwnedList::create(&mut db, None).unwrap();
+		let mut list2 = OwnedList::create(&mut db, None).unwrap();
+		assert!(list1 == list2);
+
+		for i in 0..10 {
+			list1.push(&mut db, i.into()).unwrap();
+			list2.push(&mut db, i.into()).unwrap();
+		}
+		assert!(list1 == list2);
+		assert!(list1 == DanglingList::from_leaked(list1.metadata()));
+
+		list2.set(&mut db, 9, 0.into()).unwrap();
+		assert!(list1 != list2);
+		list2.pop(&mut db).unwrap();
+		assert!(list1 != list2);
+	}
+
+	#[test]
+	fn test_set_order_independent() {
+		let mut db1 = InheritedInMemory::default();
+		let mut db2 = InheritedInMemory::default();
+		let mut vec1 = OwnedList::create(&mut db1, None).unwrap();
+		let mut vec2 = OwnedList::create(&mut db2, None).unwrap();
+
+		for i in 0..100 {
+			vec1.push(&mut db1, i.into()).unwrap();
+			vec2.push(&mut db2, Default::default()).unwrap();
+		}
+		for i in 0..100 {
+			let index = (i * 37 + 11) % 100;
+			vec2.set(&mut db2, index, index.into()).unwrap();
+		}
+		assert_eq!(vec1.root(), vec2.root());
+		assert_eq!(db1.as_ref(), db2.as_ref());
+
+		let mut db1 = UnitInMemory::default();
+		let mut db2 = UnitInMemory::default();
+		let mut vec1 = OwnedList::create(&mut db1, None).unwrap();
+		let mut vec2 = OwnedList::create(&mut db2, None).unwrap();
+
+		for i in 0..100 {
+			vec1.push(&mut db1, i.into()).unwrap();
+			vec2.push(&mut db2, Default::default()).unwrap();
+		}
+		for i in 0..100 {
+			let index = (i * 37 + 11) % 100;
+			vec2.set(&mut db2, index, index.into()).unwrap();
+		}
+		assert_eq!(vec1.root(), vec2.root());
+		assert_eq!(db1.as_ref(), db2.as_ref());
+	}
+
+	#[test]
+	fn test_populate_compact() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+		for i in 0..100 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+
+		let root = vec.root();
+		let compact = {
+			let mut proving = crate::ProvingBackend::new(&mut db);
+			let proving_vec = DanglingList::reconstruct(root.clone(), &mut proving, None).unwrap();
+			assert_eq!(proving_vec.get(&mut proving, 42).unwrap(), 42.into());
+			crate::Proofs::from(proving.into_state()).into_compact(root.clone())
+		};
+
+		let mut verifier = InheritedInMemory::default();
+		assert_eq!(verifier.populate_compact(compact), root);
+		let proven = DanglingList::reconstruct(root, &mut verifier, None).unwrap();
+		assert_eq!(proven.len(), 100);
+		assert_eq!(proven.get(&mut verifier, 42).unwrap(), 42.into());
+	}
+
+	#[test]
+	fn test_persist_root() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+		for i in 0..10 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+		let root = vec.root();
+		let other = OwnedList::from_leaked(vec.metadata());
+
+		assert_eq!(vec.persist_root(&mut db).unwrap(), root);
+		let vec = OwnedList::reconstruct(root.clone(), &mut db, None).unwrap();
+		assert_eq!(vec.len(), 10);
+		assert_eq!(vec.get(&mut db, 9).unwrap(), 9.into());
+
+		let mut empty = InheritedInMemory::default();
+		assert_eq!(other.persist_root(&mut empty), Err(Error::CorruptedDatabase));
+	}
+
+	#[test]
+	fn test_try_from_leaked() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+		for i in 0..10 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+		let (root, (inner_root, len, max_len)) = vec.metadata();
+
+		let loaded = DanglingList::try_from_leaked((root.clone(), (inner_root.clone(), len, max_len)), &mut db).unwrap();
+		assert_eq!(loaded.get(&mut db, 9).unwrap(), 9.into());
+		assert_eq!(DanglingList::try_from_leaked((root.clone(), (inner_root.clone(), 9, max_len)), &mut db).err(),
+				   Some(Error::CorruptedDatabase));
+		assert_eq!(DanglingList::try_from_leaked((root.clone(), (root.clone(), len, max_len)), &mut db).err(),
+				   Some(Error::CorruptedDatabase));
+
+		let mut empty = InheritedInMemory::default();
+		assert_eq!(DanglingList::try_from_leaked((root, (inner_root, len, max_len)), &mut empty).err(),
+				   Some(Error::CorruptedDatabase));
+	}
+
+	#[test]
+	fn test_checked_from_leaked() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+		for i in 1..=10 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+		let (root, (inner_root, len, max_len)) = vec.metadata();
+
+		let loaded = DanglingList::checked_from_leaked((root.clone(), (inner_root.clone(), len, max_len)), &mut db).unwrap();
+		assert_eq!(loaded.get(&mut db, 9).unwrap(), 10.into());
+		assert_eq!(DanglingList::checked_from_leaked((root.clone(), (inner_root.clone(), 9, max_len)), &mut db).err(),
+				   Some(Error::CorruptedDatabase));
+		assert_eq!(DanglingList::checked_from_leaked((root, (inner_root, len, Some(8))), &mut db).err(),
+				   Some(Error::CorruptedDatabase));
+	}
+
+	#[test]
+	fn test_checked_from_leaked_unit() {
+		let mut db = UnitInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+		for i in 1..=6 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+		let (root, (inner_root, len, max_len)) = vec.metadata();
+
+		let loaded = DanglingList::checked_from_leaked((root.clone(), (inner_root.clone(), len, max_len)), &mut db).unwrap();
+		assert_eq!(loaded.get(&mut db, 5).unwrap(), 6.into());
+		assert_eq!(DanglingList::checked_from_leaked((root, (inner_root, 5, max_len)), &mut db).err(),
+				   Some(Error::CorruptedDatabase));
+	}
+
+	#[test]
+	fn test_proving_reset() {
+		let mut db = InheritedInMemory::default();
+		let mut proving = crate::ProvingBackend::new(&mut db);
+		let mut vec = OwnedList::create(&mut proving, None).unwrap();
+		for i in 0..100 {
+			vec.push(&mut proving, i.into()).unwrap();
+		}
+		let root = vec.root();
+		proving.reset();
+
+		let proving_vec = DanglingList::reconstruct(root.clone(), &mut proving, None).unwrap();
+		assert_eq!(proving_vec.get(&mut proving, 42).unwrap(), 42.into());
+		let proofs = proving.reset();
+		assert!(proving.reset().is_empty());
+
+		let mut verifier = InheritedInMemory::default();
+		assert_eq!(verifier.populate_compact(proofs.into_compact(root.clone())), root);
+		let proven = DanglingList::reconstruct(root, &mut verifier, None).unwrap();
+		assert_eq!(proven.get(&mut verifier, 42).unwrap(), 42.into());
+	}
+
+	#[test]
+	fn test_prove_element() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+		for i in 0..100 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+		let root = vec.root();
+
+		let (value, compact) = vec.prove_element(&mut db, 42).unwrap();
+		assert_eq!(value, 42.into());
+		assert_eq!(compact.clone().root::<crate::InheritedDigestConstruct<Sha256, ListValue>>(), root);
+
+		let mut verifier = InheritedInMemory::default();
+		assert_eq!(verifier.populate_compact(compact), root);
+		let proven = DanglingList::reconstruct(root, &mut verifier, None).unwrap();
+		assert_eq!(proven.len(), 100);
+		assert_eq!(proven.get(&mut verifier, 42).unwrap(), 42.into());
+		assert_eq!(proven.get(&mut verifier, 90), Err(Error::CorruptedDatabase));
+
+		assert_eq!(vec.prove_element(&mut db, 100), Err(Error::AccessOverflowed));
+	}
+
+	#[test]
+	fn test_prove_element_length() {
+		let mut db = InheritedInMemory::default();
+		let mut vec = OwnedList::create(&mut db, None).unwrap();
+		for i in 0..100 {
+			vec.push(&mut db, i.into()).unwrap();
+		}
+		let root = vec.root();
+
+		let (value, compact) = vec.prove_element(&mut db, 37).unwrap();
+		assert_eq!(value, 37.into());
+		assert_eq!(compact.clone().root::<crate::InheritedDigestConstruct<Sha256, ListValue>>(), root);
+
+		let mut verifier = InheritedInMemory::default();
+		verifier.populate_compact(compact);
+		let raw = Raw::<Dangling, crate::InheritedDigestConstruct<Sha256, ListValue>>::from_leaked(root.clone());
+		assert_eq!(raw.get(&mut verifier, Index::root().right()).unwrap(), Some(100.into()));
+		let proven = DanglingList::reconstruct(root, &mut verifier, None).unwrap();
+		assert_eq!(proven.get(&mut verifier, 37).unwrap(), 37.into());
+	}
+
 	#[test]
 	fn test_deconstruct_reconstruct() {
 		let mut db = InheritedInMemory::default();
@@ -1,13 +1,20 @@
 #[cfg(feature = "std")]
 use std::collections::HashMap as Map;
+#[cfg(feature = "std")]
+use std::collections::hash_map::{Entry as MapEntry, OccupiedEntry as MapOccupiedEntry, VacantEntry as MapVacantEntry};
 #[cfg(not(feature = "std"))]
 use alloc::collections::BTreeMap as Map;
+#[cfg(not(feature = "std"))]
+use alloc::collections::btree_map::{Entry as MapEntry, OccupiedEntry as MapOccupiedEntry, VacantEntry as MapVacantEntry};
 use generic_array::GenericArray;
 use digest::Digest;
 use core::marker::PhantomData;
-use core::hash::Hash;
+use core::hash::{Hash, Hasher};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
 
-use crate::{Construct, Backend, ReadBackend, WriteBackend};
+use crate::{Construct, Backend, ReadBackend, WriteBackend, Proofs, CompactValue};
 
 /// Empty status.
 pub trait EmptyStatus {
@@ -54,6 +61,24 @@ impl<D: Digest, V> Construct for UnitDigestConstruct<D, V> where
 	}
 }
 
+/// Empty values from depth zero to `max_depth` for constructs where each
+/// empty value is the intermediate of two empty values one level down,
+/// inserting them into the backend.
+fn inherited_empty_chain<C: Construct, DB: WriteBackend<Construct=C> + ?Sized>(
+	db: &mut DB,
+	max_depth: usize
+) -> Result<Vec<C::Value>, DB::Error> {
+	let mut chain = Vec::with_capacity(max_depth + 1);
+	chain.push(C::empty_end());
+	for depth in 0..max_depth {
+		let value = (chain[depth].clone(), chain[depth].clone());
+		let key = C::intermediate_of(&value.0, &value.1);
+		db.insert(key.clone(), value)?;
+		chain.push(key);
+	}
+	Ok(chain)
+}
+
 /// Inherited Digest construct.
 pub struct InheritedDigestConstruct<D: Digest, V=GenericArray<u8, <D as Digest>::OutputSize>>(PhantomData<(D, V)>);
 
@@ -73,14 +98,139 @@ impl<D: Digest, V> Construct for InheritedDigestConstruct<D, V> where
 		db: &mut DB,
 		depth_to_bottom: usize
 	) -> Result<Self::Value, DB::Error> {
-		let mut current = Self::Value::default();
-		for _ in 0..depth_to_bottom {
-			let value = (current.clone(), current);
-			let key = Self::intermediate_of(&value.0, &value.1);
-			db.insert(key.clone(), value)?;
-			current = key;
+		let mut chain = inherited_empty_chain(db, depth_to_bottom)?;
+		Ok(chain.pop().expect("chain always contains depth zero; qed"))
+	}
+
+	fn empty_chain<DB: WriteBackend<Construct=Self> + ?Sized>(
+		db: &mut DB,
+		max_depth: usize
+	) -> Result<Vec<Self::Value>, DB::Error> {
+		inherited_empty_chain(db, max_depth)
+	}
+}
+
+/// Digest construct with domain separation between leaves and
+/// intermediates. Intermediates hash a `0x01` node-domain byte followed by
+/// both children, while leaves are stored verbatim. Empty values are
+/// inherited. Roots therefore differ from `InheritedDigestConstruct`.
+pub struct DomainSeparatedConstruct<D: Digest, V=GenericArray<u8, <D as Digest>::OutputSize>>(PhantomData<(D, V)>);
+
+impl<D: Digest, V> DomainSeparatedConstruct<D, V> {
+	/// Domain byte prepended when hashing intermediates.
+	pub const NODE_DOMAIN: u8 = 0x01;
+}
+
+impl<D: Digest, V> Construct for DomainSeparatedConstruct<D, V> where
+	V: From<GenericArray<u8, D::OutputSize>> + AsRef<[u8]> + Default + Clone,
+{
+	type Value = V;
+
+	fn intermediate_of(left: &Self::Value, right: &Self::Value) -> Self::Value {
+		let mut digest = D::new();
+		digest.input([Self::NODE_DOMAIN]);
+		digest.input(left.as_ref());
+		digest.input(right.as_ref());
+		digest.result().into()
+	}
+
+	fn empty_at<DB: WriteBackend<Construct=Self> + ?Sized>(
+		db: &mut DB,
+		depth_to_bottom: usize
+	) -> Result<Self::Value, DB::Error> {
+		let mut chain = inherited_empty_chain(db, depth_to_bottom)?;
+		Ok(chain.pop().expect("chain always contains depth zero; qed"))
+	}
+
+	fn empty_chain<DB: WriteBackend<Construct=Self> + ?Sized>(
+		db: &mut DB,
+		max_depth: usize
+	) -> Result<Vec<Self::Value>, DB::Error> {
+		inherited_empty_chain(db, max_depth)
+	}
+}
+
+/// Construct wrapping `Inner`, with intermediates and empty values
+/// unchanged, that can build leaves from byte inputs longer than a value
+/// by hashing them down with `D`.
+pub struct PrehashConstruct<D: Digest, Inner: Construct>(PhantomData<(D, Inner)>);
+
+impl<D: Digest, Inner: Construct> PrehashConstruct<D, Inner> where
+	Inner::Value: From<GenericArray<u8, D::OutputSize>> + AsMut<[u8]>,
+{
+	/// Build a leaf from bytes. Inputs fitting in a value are zero-padded
+	/// verbatim, and longer inputs are hashed.
+	pub fn leaf_of(bytes: &[u8]) -> Inner::Value {
+		let mut ret = Inner::Value::default();
+		if bytes.len() <= ret.as_mut().len() {
+			ret.as_mut()[..bytes.len()].copy_from_slice(bytes);
+			ret
+		} else {
+			D::digest(bytes).into()
 		}
-		Ok(current)
+	}
+}
+
+impl<D: Digest, Inner: Construct> Construct for PrehashConstruct<D, Inner> {
+	type Value = Inner::Value;
+
+	fn intermediate_of(left: &Self::Value, right: &Self::Value) -> Self::Value {
+		Inner::intermediate_of(left, right)
+	}
+
+	fn hash_leaf(value: &Self::Value) -> Self::Value {
+		Inner::hash_leaf(value)
+	}
+
+	fn empty_end() -> Self::Value {
+		Inner::empty_end()
+	}
+
+	fn empty_at<DB: WriteBackend<Construct=Self> + ?Sized>(
+		db: &mut DB,
+		depth_to_bottom: usize
+	) -> Result<Self::Value, DB::Error> {
+		Inner::empty_at(&mut InnerBackend::<_, Inner>(db, PhantomData), depth_to_bottom)
+	}
+
+	fn empty_chain<DB: WriteBackend<Construct=Self> + ?Sized>(
+		db: &mut DB,
+		max_depth: usize
+	) -> Result<Vec<Self::Value>, DB::Error> {
+		Inner::empty_chain(&mut InnerBackend::<_, Inner>(db, PhantomData), max_depth)
+	}
+}
+
+/// Backend exposing a `PrehashConstruct` backend as one of its inner
+/// construct. Values are of the same type, so all calls are forwarded.
+struct InnerBackend<'a, DB: ?Sized, Inner>(&'a mut DB, PhantomData<Inner>);
+
+impl<'a, D: Digest, Inner: Construct, DB: Backend<Construct=PrehashConstruct<D, Inner>> + ?Sized> Backend for InnerBackend<'a, DB, Inner> {
+	type Construct = Inner;
+	type Error = DB::Error;
+}
+
+impl<'a, D: Digest, Inner: Construct, DB: ReadBackend<Construct=PrehashConstruct<D, Inner>> + ?Sized> ReadBackend for InnerBackend<'a, DB, Inner> {
+	fn get(&mut self, key: &Inner::Value) -> Result<Option<(Inner::Value, Inner::Value)>, Self::Error> {
+		self.0.get(key)
+	}
+}
+
+impl<'a, D: Digest, Inner: Construct, DB: WriteBackend<Construct=PrehashConstruct<D, Inner>> + ?Sized> WriteBackend for InnerBackend<'a, DB, Inner> {
+	fn rootify(&mut self, key: &Inner::Value) -> Result<(), Self::Error> {
+		self.0.rootify(key)
+	}
+
+	fn unrootify(&mut self, key: &Inner::Value) -> Result<(), Self::Error> {
+		self.0.unrootify(key)
+	}
+
+	fn insert(
+		&mut self,
+		key: Inner::Value,
+		value: (Inner::Value, Inner::Value)
+	) -> Result<(), Self::Error> {
+		self.0.insert(key, value)
 	}
 }
 
@@ -142,6 +292,230 @@ impl<C: Construct> WriteBackend for NoopBackend<C> {
 	}
 }
 
+/// FNV-1a hasher, used to index interned values without storing them a
+/// second time as map keys.
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+	fn default() -> Self {
+		FnvHasher(0xcbf29ce484222325)
+	}
+}
+
+impl Hasher for FnvHasher {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.0 ^= u64::from(*byte);
+			self.0 = self.0.wrapping_mul(0x100000001b3);
+		}
+	}
+}
+
+/// In-memory merkle database interning values. Each distinct value, node
+/// key or child, is stored once behind a handle, and nodes refer to their
+/// children by handle, which saves space for trees with many duplicate
+/// leaves. Values are found through a table from their hash to their
+/// handle, probing the following hashes on collision. Values are
+/// reference counted as in `InMemoryBackend`, and handles of removed
+/// values are reused. Inserting fails once the handle space is exhausted.
+pub struct InterningBackend<C: Construct> {
+	entries: Vec<Option<InterningEntry<C::Value>>>,
+	free: Vec<u32>,
+	slots: Map<u64, u32>,
+	len: usize,
+}
+
+struct InterningEntry<V> {
+	value: V,
+	children: Option<(u32, u32)>,
+	count: usize,
+}
+
+impl<C: Construct> Default for InterningBackend<C> where
+	C::Value: Eq + Hash
+{
+	fn default() -> Self {
+		Self {
+			entries: Vec::new(),
+			free: Vec::new(),
+			slots: Map::default(),
+			len: 0,
+		}
+	}
+}
+
+impl<C: Construct> InterningBackend<C> where
+	C::Value: Eq + Hash
+{
+	/// Number of stored nodes.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether no node is stored.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Number of distinct values stored, including node keys.
+	pub fn interned_len(&self) -> usize {
+		self.entries.len() - self.free.len()
+	}
+
+	fn slot_of(value: &C::Value) -> u64 {
+		let mut hasher = FnvHasher::default();
+		value.hash(&mut hasher);
+		hasher.finish()
+	}
+
+	fn entry(&self, handle: u32) -> &InterningEntry<C::Value> {
+		self.entries[handle as usize].as_ref().expect("handles in use are always live; qed")
+	}
+
+	fn entry_mut(&mut self, handle: u32) -> &mut InterningEntry<C::Value> {
+		self.entries[handle as usize].as_mut().expect("handles in use are always live; qed")
+	}
+
+	/// Find the handle of a value, or the free slot to store it at.
+	fn lookup(&self, value: &C::Value) -> Result<u32, u64> {
+		let mut slot = Self::slot_of(value);
+		loop {
+			match self.slots.get(&slot) {
+				Some(handle) if &self.entry(*handle).value == value => return Ok(*handle),
+				Some(_) => slot = slot.wrapping_add(1),
+				None => return Err(slot),
+			}
+		}
+	}
+
+	fn intern(&mut self, value: C::Value) -> Result<u32, ()> {
+		let slot = match self.lookup(&value) {
+			Ok(handle) => return Ok(handle),
+			Err(slot) => slot,
+		};
+
+		let entry = InterningEntry { value, children: None, count: 0 };
+		let handle = match self.free.pop() {
+			Some(handle) => {
+				self.entries[handle as usize] = Some(entry);
+				handle
+			},
+			None => {
+				if self.entries.len() >= u32::MAX as usize {
+					return Err(())
+				}
+
+				self.entries.push(Some(entry));
+				(self.entries.len() - 1) as u32
+			},
+		};
+		self.slots.insert(slot, handle);
+		Ok(handle)
+	}
+
+	/// Decrease the reference count of a value, removing it and releasing
+	/// its children once it drops to zero.
+	fn release(&mut self, handle: u32) {
+		let entry = self.entry_mut(handle);
+		entry.count = entry.count.saturating_sub(1);
+		if entry.count > 0 {
+			return
+		}
+
+		let entry = self.entries[handle as usize].take().expect("handles in use are always live; qed");
+		self.free.push(handle);
+
+		let mut slot = Self::slot_of(&entry.value);
+		while self.slots.get(&slot) != Some(&handle) {
+			slot = slot.wrapping_add(1);
+		}
+		self.slots.remove(&slot);
+
+		// Values probed past the freed slot would no longer be found, so
+		// store them again from their own hash.
+		let mut next = slot.wrapping_add(1);
+		while let Some(moved) = self.slots.remove(&next) {
+			let mut slot = Self::slot_of(&self.entry(moved).value);
+			while self.slots.contains_key(&slot) {
+				slot = slot.wrapping_add(1);
+			}
+			self.slots.insert(slot, moved);
+			next = next.wrapping_add(1);
+		}
+
+		if let Some((left, right)) = entry.children {
+			self.len -= 1;
+			self.release(left);
+			self.release(right);
+		}
+	}
+}
+
+impl<C: Construct> Backend for InterningBackend<C> where
+	C::Value: Eq + Hash
+{
+	type Construct = C;
+	type Error = ();
+}
+
+impl<C: Construct> ReadBackend for InterningBackend<C> where
+	C::Value: Eq + Hash
+{
+	fn get(
+		&mut self,
+		key: &C::Value,
+	) -> Result<Option<(C::Value, C::Value)>, Self::Error> {
+		let handle = match self.lookup(key) {
+			Ok(handle) => handle,
+			Err(_) => return Ok(None),
+		};
+
+		Ok(self.entry(handle).children.map(|(left, right)| {
+			(self.entry(left).value.clone(), self.entry(right).value.clone())
+		}))
+	}
+}
+
+impl<C: Construct> WriteBackend for InterningBackend<C> where
+	C::Value: Eq + Hash
+{
+	fn rootify(&mut self, key: &C::Value) -> Result<(), Self::Error> {
+		let handle = self.intern(key.clone())?;
+		self.entry_mut(handle).count += 1;
+		Ok(())
+	}
+
+	fn unrootify(&mut self, key: &C::Value) -> Result<(), Self::Error> {
+		if let Ok(handle) = self.lookup(key) {
+			self.release(handle);
+		}
+		Ok(())
+	}
+
+	fn insert(
+		&mut self,
+		key: C::Value,
+		value: (C::Value, C::Value)
+	) -> Result<(), Self::Error> {
+		let key = self.intern(key)?;
+		if self.entry(key).children.is_some() {
+			return Ok(())
+		}
+
+		let left = self.intern(value.0)?;
+		self.entry_mut(left).count += 1;
+		let right = self.intern(value.1)?;
+		self.entry_mut(right).count += 1;
+		self.entry_mut(key).children = Some((left, right));
+		self.len += 1;
+		Ok(())
+	}
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 /// In-memory DB error.
 pub enum InMemoryBackendError {
@@ -163,10 +537,77 @@ impl std::fmt::Display for InMemoryBackendError {
 #[cfg(feature = "std")]
 impl std::error::Error for InMemoryBackendError { }
 
+/// Callback invoked with an evicted node.
+pub type EvictCallback<V> = Box<dyn FnMut(V, (V, V))>;
+
 /// In-memory merkle database.
-pub struct InMemoryBackend<C: Construct>(
-	Map<C::Value, (Option<(C::Value, C::Value)>, Option<usize>)>,
-);
+pub struct InMemoryBackend<C: Construct> {
+	map: Map<C::Value, (Option<(C::Value, C::Value)>, Option<usize>)>,
+	capacity: Option<usize>,
+	inserted: VecDeque<C::Value>,
+	evict_callback: Option<EvictCallback<C::Value>>,
+}
+
+/// Node stored in the in-memory backend: its children, if any, and its
+/// reference count, `None` if it is never removed.
+pub type InMemoryNode<V> = (Option<(V, V)>, Option<usize>);
+
+/// Entry of a node in the in-memory backend.
+pub enum InMemoryEntry<'a, C: Construct> {
+	/// The node is stored.
+	Occupied(InMemoryOccupiedEntry<'a, C>),
+	/// The node is not stored.
+	Vacant(InMemoryVacantEntry<'a, C>),
+}
+
+/// Entry of a stored node.
+pub struct InMemoryOccupiedEntry<'a, C: Construct>(MapOccupiedEntry<'a, C::Value, InMemoryNode<C::Value>>);
+
+impl<'a, C: Construct> InMemoryOccupiedEntry<'a, C> where
+	C::Value: Eq + Hash + Ord,
+{
+	/// Key of the entry.
+	pub fn key(&self) -> &C::Value {
+		self.0.key()
+	}
+
+	/// Reference to the stored node.
+	pub fn get(&self) -> &InMemoryNode<C::Value> {
+		self.0.get()
+	}
+
+	/// Mutable reference to the stored node.
+	pub fn get_mut(&mut self) -> &mut InMemoryNode<C::Value> {
+		self.0.get_mut()
+	}
+
+	/// Convert into a mutable reference to the stored node.
+	pub fn into_mut(self) -> &'a mut InMemoryNode<C::Value> {
+		self.0.into_mut()
+	}
+
+	/// Remove the node, without releasing its children.
+	pub fn remove(self) -> InMemoryNode<C::Value> {
+		self.0.remove()
+	}
+}
+
+/// Entry of a node that is not stored.
+pub struct InMemoryVacantEntry<'a, C: Construct>(MapVacantEntry<'a, C::Value, InMemoryNode<C::Value>>);
+
+impl<'a, C: Construct> InMemoryVacantEntry<'a, C> where
+	C::Value: Eq + Hash + Ord,
+{
+	/// Key of the entry.
+	pub fn key(&self) -> &C::Value {
+		self.0.key()
+	}
+
+	/// Store the node, without referencing its children.
+	pub fn insert(self, node: InMemoryNode<C::Value>) -> &'a mut InMemoryNode<C::Value> {
+		self.0.insert(node)
+	}
+}
 
 impl<C: Construct> Default for InMemoryBackend<C> where
 	C::Value: Eq + Hash + Ord
@@ -175,54 +616,205 @@ impl<C: Construct> Default for InMemoryBackend<C> where
 		let mut map = Map::default();
 		map.insert(Default::default(), (None, None));
 
-		Self(map)
+		Self {
+			map,
+			capacity: None,
+			inserted: VecDeque::new(),
+			evict_callback: None,
+		}
 	}
 }
 
+/// The eviction callback is not carried over to the clone.
 impl<C: Construct> Clone for InMemoryBackend<C> {
 	fn clone(&self) -> Self {
-		Self(self.0.clone())
+		Self {
+			map: self.map.clone(),
+			capacity: self.capacity,
+			inserted: self.inserted.clone(),
+			evict_callback: None,
+		}
 	}
 }
 
 impl<C: Construct> InMemoryBackend<C> where
 	C::Value: Eq + Hash + Ord,
 {
-	fn remove(&mut self, old_key: &C::Value) -> Result<(), InMemoryBackendError> {
-		let (old_value, to_remove) = {
-			let value = match self.0.get_mut(old_key) {
-				Some(value) => value,
-				None => return Ok(()),
-			};
-			value.1.as_mut().map(|v| *v -= 1);
-			(value.0.clone(), value.1.map(|v| v == 0).unwrap_or(false))
+	// Takes the key by value, so that the recursive calls can move the
+	// children out of the removed node instead of cloning them.
+	fn remove(&mut self, old_key: C::Value) -> Result<(), InMemoryBackendError> {
+		let mut entry = match self.entry(old_key) {
+			InMemoryEntry::Occupied(entry) => entry,
+			InMemoryEntry::Vacant(_) => return Ok(()),
 		};
+		entry.get_mut().1.as_mut().map(|v| *v -= 1);
 
-		if to_remove {
-			if let Some(old_value) = old_value {
-				self.remove(&old_value.0)?;
-				self.remove(&old_value.1)?;
+		if entry.get().1 == Some(0) {
+			if let (Some(old_value), _) = entry.remove() {
+				self.remove(old_value.0)?;
+				self.remove(old_value.1)?;
 			}
-
-			self.0.remove(old_key);
 		}
 
 		Ok(())
 	}
 
+	/// Get the entry of a node for in-place modification.
+	pub fn entry(&mut self, key: C::Value) -> InMemoryEntry<'_, C> {
+		match self.map.entry(key) {
+			MapEntry::Occupied(entry) => InMemoryEntry::Occupied(InMemoryOccupiedEntry(entry)),
+			MapEntry::Vacant(entry) => InMemoryEntry::Vacant(InMemoryVacantEntry(entry)),
+		}
+	}
+
+	/// Create a new backend holding at most `capacity` stored nodes. When
+	/// it is exceeded, the least-recently-inserted nodes that are neither
+	/// rootified nor referenced by another node are evicted, oldest first.
+	/// Eviction only happens on `rootify` and `shrink`, never while a tree
+	/// is being inserted, so the capacity may be exceeded in between.
+	/// Rootified nodes and their descendants are never evicted.
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			capacity: Some(capacity),
+			..Self::default()
+		}
+	}
+
+	/// Set the callback invoked with each evicted node, for example to
+	/// spill it to disk.
+	pub fn set_evict_callback<F: FnMut(C::Value, (C::Value, C::Value)) + 'static>(&mut self, f: F) {
+		self.evict_callback = Some(Box::new(f));
+	}
+
+	/// Remove all nodes regardless of their reference counts, leaving the
+	/// backend as newly created. Any tree handle on it becomes dangling.
+	pub fn clear(&mut self) {
+		self.map.clear();
+		self.map.insert(Default::default(), (None, None));
+		self.inserted.clear();
+	}
+
+	/// Unrootify the given root, removing any of its nodes no longer
+	/// referenced.
+	pub fn drop_root(&mut self, root: &C::Value) -> Result<(), InMemoryBackendError> {
+		self.remove(root.clone())
+	}
+
+	fn release(&mut self, key: &C::Value) {
+		let to_remove = match self.map.get_mut(key) {
+			Some(value) => {
+				if let Some(count) = value.1.as_mut() {
+					*count -= 1;
+				}
+				value.0.is_none() && value.1 == Some(0)
+			},
+			None => false,
+		};
+
+		if to_remove {
+			self.map.remove(key);
+		}
+	}
+
+	/// Evict unreferenced nodes until the capacity is met, if one is set.
+	/// Nodes inserted but not yet reachable from a root are evicted too,
+	/// so this should not be called while a tree is being built.
+	pub fn shrink(&mut self) {
+		let capacity = match self.capacity {
+			Some(capacity) if self.map.len() > capacity => capacity,
+			_ => return,
+		};
+
+		// Evicting a node may release its children, which were inserted
+		// earlier, so scan again until nothing more can be evicted.
+		let mut inserted = core::mem::take(&mut self.inserted);
+		let mut evicted = true;
+		while evicted && self.map.len() > capacity {
+			evicted = false;
+			inserted.retain(|key| {
+				if self.map.len() <= capacity {
+					return self.map.contains_key(key)
+				}
+
+				match self.map.get(key) {
+					Some((Some(_), Some(0))) => (),
+					Some(_) => return true,
+					None => return false,
+				}
+
+				if let Some((Some((left, right)), _)) = self.map.remove(key) {
+					self.release(&left);
+					self.release(&right);
+
+					if let Some(callback) = self.evict_callback.as_mut() {
+						callback(key.clone(), (left, right));
+					}
+				}
+				evicted = true;
+				false
+			});
+		}
+		self.inserted = inserted;
+	}
+
 	/// Populate the database with proofs.
 	pub fn populate(&mut self, proofs: Map<C::Value, (C::Value, C::Value)>) {
 		for (key, (left, right)) in proofs {
-			self.0.insert(key, (Some((left.clone(), right.clone())), None));
-			self.0.entry(left).or_insert((None, None));
-			self.0.entry(right).or_insert((None, None));
+			self.map.insert(key, (Some((left.clone(), right.clone())), None));
+			self.map.entry(left).or_insert((None, None));
+			self.map.entry(right).or_insert((None, None));
+		}
+	}
+
+	/// Populate the database with compact proofs, returning the root.
+	pub fn populate_compact(&mut self, compact: CompactValue<C::Value>) -> C::Value {
+		let (proofs, root) = Proofs::from_compact::<C>(compact);
+		self.populate(proofs.into());
+		root
+	}
+
+	/// Verify every stored node against its children with
+	/// `Construct::verify_intermediate`, returning the inconsistent keys
+	/// otherwise.
+	pub fn verify_integrity(&self) -> Result<(), Vec<C::Value>> {
+		let invalid = self.map.iter()
+			.filter_map(|(key, (value, _))| match value {
+				Some((left, right)) if !C::verify_intermediate(key, left, right) => Some(key.clone()),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+
+		if invalid.is_empty() {
+			Ok(())
+		} else {
+			Err(invalid)
 		}
 	}
 }
 
+impl<D: Digest, V> InMemoryBackend<UnitDigestConstruct<D, V>> where
+	V: From<GenericArray<u8, D::OutputSize>> + AsRef<[u8]> + Default + Clone + Eq + Hash + Ord,
+{
+	/// Create a new backend where all empty subtrees, regardless of
+	/// depth, are represented by the default value.
+	pub fn new_with_unit_empty() -> Self {
+		Self::default()
+	}
+}
+
+impl<D: Digest, V> InMemoryBackend<InheritedDigestConstruct<D, V>> where
+	V: From<GenericArray<u8, D::OutputSize>> + AsRef<[u8]> + Default + Clone + Eq + Hash + Ord,
+{
+	/// Create a new backend where empty subtrees are hashes of their
+	/// empty children. This is the ssz compliant variant.
+	pub fn new_with_inherited_empty() -> Self {
+		Self::default()
+	}
+}
+
 impl<C: Construct> AsRef<Map<C::Value, (Option<(C::Value, C::Value)>, Option<usize>)>> for InMemoryBackend<C> {
 	fn as_ref(&self) -> &Map<C::Value, (Option<(C::Value, C::Value)>, Option<usize>)> {
-		&self.0
+		&self.map
 	}
 }
 
@@ -235,7 +827,7 @@ impl<C: Construct> ReadBackend for InMemoryBackend<C> where
 	C::Value: Eq + Hash + Ord,
 {
 	fn get(&mut self, key: &C::Value) -> Result<Option<(C::Value, C::Value)>, Self::Error> {
-		Ok(self.0.get(key).map(|v| v.0.clone()).unwrap_or(None))
+		Ok(self.map.get(key).map(|v| v.0.clone()).unwrap_or(None))
 	}
 }
 
@@ -243,13 +835,22 @@ impl<C: Construct> WriteBackend for InMemoryBackend<C> where
 	C::Value: Eq + Hash + Ord,
 {
 	fn rootify(&mut self, key: &C::Value) -> Result<(), Self::Error> {
-		self.0.entry(key.clone()).or_insert((None, Some(0))).1
-			.as_mut().map(|v| *v += 1);
+		match self.entry(key.clone()) {
+			InMemoryEntry::Occupied(mut entry) => {
+				if let Some(count) = entry.get_mut().1.as_mut() {
+					*count += 1;
+				}
+			},
+			InMemoryEntry::Vacant(entry) => {
+				entry.insert((None, Some(1)));
+			},
+		}
+		self.shrink();
 		Ok(())
 	}
 
 	fn unrootify(&mut self, key: &C::Value) -> Result<(), Self::Error> {
-		self.remove(key)?;
+		self.remove(key.clone())?;
 		Ok(())
 	}
 
@@ -258,18 +859,338 @@ impl<C: Construct> WriteBackend for InMemoryBackend<C> where
 		key: C::Value,
 		value: (C::Value, C::Value)
 	) -> Result<(), Self::Error> {
-		if self.0.contains_key(&key) {
+		if self.map.contains_key(&key) {
 			return Ok(())
 		}
 
 		let (left, right) = value;
 
-		self.0.entry(left.clone()).or_insert((None, Some(0))).1
+		self.map.entry(left.clone()).or_insert((None, Some(0))).1
 			.as_mut().map(|v| *v += 1);
-		self.0.entry(right.clone()).or_insert((None, Some(0))).1
+		self.map.entry(right.clone()).or_insert((None, Some(0))).1
 			.as_mut().map(|v| *v += 1);
 
-		self.0.insert(key, (Some((left, right)), Some(0)));
+		self.map.insert(key.clone(), (Some((left, right)), Some(0)));
+		if self.capacity.is_some() {
+			self.inserted.push_back(key);
+		}
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{OwnedVector, Tree};
+	use sha2::Sha256;
+
+	type Prehash = PrehashConstruct<Sha256, InheritedDigestConstruct<Sha256>>;
+	type InMemory = InMemoryBackend<InheritedDigestConstruct<Sha256>>;
+
+	#[test]
+	fn test_verify_integrity() {
+		let mut db = InMemory::default();
+		let vec = OwnedVector::create_with(&mut db, 8, None, |i| {
+			let mut value = GenericArray::default();
+			value[0] = i as u8;
+			value
+		}).unwrap();
+		assert_eq!(db.verify_integrity(), Ok(()));
+
+		let root = vec.root();
+		let (left, right) = db.as_ref().get(&root).unwrap().0.unwrap();
+		db.map.insert(root, (Some((right, left)), Some(1)));
+		assert_eq!(db.verify_integrity(), Err(vec![root]));
+	}
+
+	#[test]
+	fn test_verify_integrity_custom() {
+		struct Symmetric;
+
+		impl Construct for Symmetric {
+			type Value = <InheritedDigestConstruct<Sha256> as Construct>::Value;
+
+			fn intermediate_of(left: &Self::Value, right: &Self::Value) -> Self::Value {
+				InheritedDigestConstruct::<Sha256>::intermediate_of(left, right)
+			}
+
+			fn verify_intermediate(key: &Self::Value, left: &Self::Value, right: &Self::Value) -> bool {
+				&Self::intermediate_of(left, right) == key || &Self::intermediate_of(right, left) == key
+			}
+
+			fn empty_at<DB: WriteBackend<Construct=Self> + ?Sized>(
+				_db: &mut DB,
+				_depth_to_bottom: usize
+			) -> Result<Self::Value, DB::Error> {
+				Ok(Default::default())
+			}
+		}
+
+		let mut db = InMemoryBackend::<Symmetric>::default();
+		let vec = OwnedVector::create_with(&mut db, 8, None, |i| GenericArray::from([i as u8 + 1; 32])).unwrap();
+
+		let root = vec.root();
+		let (left, right) = db.as_ref().get(&root).unwrap().0.unwrap();
+		db.map.insert(root, (Some((right, left)), Some(1)));
+		assert_eq!(db.verify_integrity(), Ok(()));
+	}
+
+	#[test]
+	fn test_entry() {
+		let mut db = InMemory::default();
+		let vec = OwnedVector::create_with(&mut db, 2, None, |i| GenericArray::from([i as u8 + 1; 32])).unwrap();
+		let root = vec.root();
+
+		match db.entry(root) {
+			InMemoryEntry::Occupied(mut entry) => {
+				assert_eq!(entry.key(), &root);
+				assert_eq!(entry.get().1, Some(1));
+				if let Some(count) = entry.get_mut().1.as_mut() {
+					*count += 1;
+				}
+			},
+			InMemoryEntry::Vacant(_) => panic!("root is stored"),
+		}
+		assert_eq!(db.as_ref().get(&root).unwrap().1, Some(2));
+
+		db.unrootify(&root).unwrap();
+		assert!(db.get(&root).unwrap().is_some());
+		vec.drop(&mut db).unwrap();
+		assert!(db.get(&root).unwrap().is_none());
+
+		let key = GenericArray::from([9; 32]);
+		match db.entry(key) {
+			InMemoryEntry::Vacant(entry) => { entry.insert((None, Some(0))); },
+			InMemoryEntry::Occupied(_) => panic!("key is not stored"),
+		}
+		assert_eq!(db.as_ref().get(&key), Some(&(None, Some(0))));
+	}
+
+	#[test]
+	fn test_clear_drop_root() {
+		let mut db = InMemory::default();
+		let vec = OwnedVector::create_with(&mut db, 8, None, |i| GenericArray::from([i as u8 + 1; 32])).unwrap();
+		let other = OwnedVector::create_with(&mut db, 4, None, |i| GenericArray::from([i as u8 + 1; 32])).unwrap();
+		let root = vec.root();
+
+		db.drop_root(&root).unwrap();
+		assert!(db.get(&root).unwrap().is_none());
+		assert!(db.get(&other.root()).unwrap().is_some());
+
+		db.clear();
+		assert_eq!(db.as_ref(), InMemory::default().as_ref());
+	}
+
+	#[test]
+	fn test_interning() {
+		let leaf = |i: usize| if i & 15 == 0 { GenericArray::from([(i / 16) as u8 + 1; 32]) } else { Default::default() };
+
+		let mut db = InMemory::default();
+		let vec = OwnedVector::create_with(&mut db, 1024, None, leaf).unwrap();
+		let mut interning = InterningBackend::<InheritedDigestConstruct<Sha256>>::default();
+		let interned = OwnedVector::create_with(&mut interning, 1024, None, leaf).unwrap();
+		assert_eq!(interned.root(), vec.root());
+		for i in (0..1024).step_by(7) {
+			assert_eq!(interned.get(&mut interning, i).unwrap(), leaf(i));
+		}
+
+		// InMemory stores a node's children next to its key, while interned
+		// nodes refer to the single copy of each value.
+		let nodes = db.as_ref().values().filter(|(children, _)| children.is_some()).count();
+		assert_eq!(interning.len(), nodes);
+		assert_eq!(interning.interned_len(), db.as_ref().len());
+		assert!(interning.interned_len() * 2 < db.as_ref().len() + 2 * nodes);
+
+		interned.drop(&mut interning).unwrap();
+		vec.drop(&mut db).unwrap();
+		let nodes = db.as_ref().values().filter(|(children, _)| children.is_some()).count();
+		assert_eq!(interning.len(), nodes);
+		assert_eq!(interning.interned_len(), db.as_ref().len());
+	}
+
+	#[test]
+	fn test_interning_refcount() {
+		let leaf = |i: usize| GenericArray::from([(i % 4) as u8; 32]);
+
+		let mut db = InMemory::default();
+		let mut vec = OwnedVector::create_with(&mut db, 64, None, leaf).unwrap();
+		let mut interning = InterningBackend::<InheritedDigestConstruct<Sha256>>::default();
+		let mut interned = OwnedVector::create_with(&mut interning, 64, None, leaf).unwrap();
+		for i in 0..16 {
+			vec.set(&mut db, i * 4, leaf(i + 1)).unwrap();
+			interned.set(&mut interning, i * 4, leaf(i + 1)).unwrap();
+		}
+		for i in 0..64 {
+			assert_eq!(interned.get(&mut interning, i).unwrap(), vec.get(&mut db, i).unwrap());
+		}
+
+		assert_eq!(interned.root(), vec.root());
+		let nodes = db.as_ref().values().filter(|(children, _)| children.is_some()).count();
+		assert_eq!(interning.len(), nodes);
+		assert_eq!(interning.interned_len(), db.as_ref().len());
+	}
+
+	#[test]
+	fn test_empty_end() {
+		let mut db = InMemory::default();
+		assert_eq!(InheritedDigestConstruct::<Sha256>::empty_end(), InheritedDigestConstruct::<Sha256>::empty_at(&mut db, 0).unwrap());
+		assert_eq!(Prehash::empty_end(), Prehash::empty_at(&mut InMemoryBackend::<Prehash>::default(), 0).unwrap());
+		let mut db = InMemoryBackend::<UnitDigestConstruct<Sha256>>::default();
+		assert_eq!(UnitDigestConstruct::<Sha256>::empty_end(), UnitDigestConstruct::<Sha256>::empty_at(&mut db, 0).unwrap());
+	}
+
+	#[test]
+	fn test_empty_chain() {
+		let mut db = InMemory::default();
+		let chain = InheritedDigestConstruct::<Sha256>::empty_chain(&mut db, 5).unwrap();
+		assert_eq!(chain.len(), 6);
+		let mut other = InMemory::default();
+		for (depth, value) in chain.iter().enumerate() {
+			assert_eq!(*value, InheritedDigestConstruct::<Sha256>::empty_at(&mut other, depth).unwrap());
+		}
+		assert_eq!(db.as_ref(), other.as_ref());
+
+		let mut db = InMemoryBackend::<Prehash>::default();
+		assert_eq!(Prehash::empty_chain(&mut db, 5).unwrap(), chain);
+		let mut db = InMemoryBackend::<UnitDigestConstruct<Sha256>>::default();
+		assert_eq!(UnitDigestConstruct::<Sha256>::empty_chain(&mut db, 2).unwrap(), vec![Default::default(); 3]);
+	}
+
+	#[test]
+	fn test_capacity_eviction() {
+		use std::rc::Rc;
+		use std::cell::RefCell;
+
+		let evicted = Rc::new(RefCell::new(Vec::new()));
+		let mut db = InMemory::with_capacity(24);
+		let recorded = evicted.clone();
+		db.set_evict_callback(move |key, _| recorded.borrow_mut().push(key));
+
+		let vec = OwnedVector::create_with(&mut db, 8, None, |i| {
+			let mut value = GenericArray::default();
+			value[0] = i as u8;
+			value
+		}).unwrap();
+		let rooted = vec.root();
+		assert!(evicted.borrow().is_empty());
+
+		let mut unreferenced = Vec::new();
+		for i in 0..20u8 {
+			let left = GenericArray::from([i; 32]);
+			let right = GenericArray::from([i.wrapping_add(100); 32]);
+			let key = InheritedDigestConstruct::<Sha256>::intermediate_of(&left, &right);
+			db.insert(key, (left, right)).unwrap();
+			unreferenced.push(key);
+		}
+		assert!(evicted.borrow().is_empty());
+		db.shrink();
+
+		let evicted = evicted.borrow().iter()
+			.filter(|key| unreferenced.contains(key))
+			.cloned()
+			.collect::<Vec<_>>();
+		assert!(!evicted.is_empty());
+		assert_eq!(&evicted[..], &unreferenced[..evicted.len()]);
+		assert!(db.get(&unreferenced[0]).unwrap().is_none());
+		assert!(db.get(&unreferenced[19]).unwrap().is_some());
+
+		assert!(db.get(&rooted).unwrap().is_some());
+		for i in 0..8 {
+			let mut value = GenericArray::default();
+			value[0] = i as u8;
+			assert_eq!(vec.get(&mut db, i).unwrap(), value);
+		}
+	}
+
+	#[test]
+	fn test_capacity_eviction_releases_children() {
+		let leaf = |i: u8| GenericArray::from([i; 32]);
+		let node = |left, right| (InheritedDigestConstruct::<Sha256>::intermediate_of(&left, &right), (left, right));
+
+		let mut db = InMemory::with_capacity(3);
+		let (left, left_children) = node(leaf(1), leaf(2));
+		let (right, right_children) = node(leaf(3), leaf(4));
+		let (parent, parent_children) = node(left, right);
+		db.insert(left, left_children).unwrap();
+		db.insert(right, right_children).unwrap();
+		db.insert(parent, parent_children).unwrap();
+		assert_eq!(db.as_ref().len(), 8);
+
+		db.rootify(&leaf(5)).unwrap();
+		assert!(db.as_ref().len() <= 3);
+		assert!(db.get(&parent).unwrap().is_none());
+		assert!(db.get(&left).unwrap().is_none());
+		assert!(db.get(&right).unwrap().is_none());
+	}
+
+	#[test]
+	fn test_capacity_large_tree() {
+		let value = |i: usize| {
+			let mut value = GenericArray::default();
+			value[0] = i as u8;
+			value
+		};
+
+		let mut db = InMemory::with_capacity(16);
+		let mut vec = OwnedVector::create_with(&mut db, 64, None, value).unwrap();
+		for i in 0..64 {
+			assert_eq!(vec.get(&mut db, i).unwrap(), value(i));
+		}
+
+		for i in 0..64 {
+			vec.set(&mut db, i, value(i + 1)).unwrap();
+		}
+		for i in 0..64 {
+			assert_eq!(vec.get(&mut db, i).unwrap(), value(i + 1));
+		}
+	}
+
+	#[test]
+	fn test_domain_separated() {
+		type Separated = DomainSeparatedConstruct<Sha256>;
+
+		let mut db = InMemoryBackend::<Separated>::default();
+		let vec = OwnedVector::create_with(&mut db, 2, None, |i| {
+			GenericArray::from([i as u8 + 1; 32])
+		}).unwrap();
+		assert_eq!(&vec.root()[..], &[
+			0xb3, 0x31, 0xda, 0x6e, 0xc4, 0x9d, 0x45, 0x47, 0xd9, 0x94, 0x2a, 0x67, 0x27, 0xe5, 0x12, 0x3f,
+			0x69, 0xbe, 0xd5, 0xa0, 0xb9, 0x7a, 0xc1, 0x71, 0xcf, 0xbf, 0xd6, 0x20, 0x14, 0x31, 0xfc, 0xfa,
+		][..]);
+		assert_ne!(vec.root(), InheritedDigestConstruct::<Sha256>::intermediate_of(
+			&GenericArray::from([1; 32]), &GenericArray::from([2; 32])
+		));
+
+		let padded = OwnedVector::create_with(&mut db, 4, None, |i| {
+			if i < 2 { GenericArray::from([i as u8 + 1; 32]) } else { Default::default() }
+		}).unwrap();
+		assert_eq!(padded.root(), Separated::intermediate_of(&vec.root(), &Separated::empty_at(&mut db, 1).unwrap()));
+		assert_eq!(&padded.root()[..], &[
+			0xba, 0x7b, 0x53, 0x82, 0xb4, 0xdb, 0xf7, 0x5d, 0x51, 0x28, 0x7f, 0x7b, 0xcd, 0x8d, 0xad, 0x23,
+			0xb2, 0x1e, 0x1e, 0x8b, 0xf6, 0x99, 0xc6, 0x2f, 0xc2, 0xe9, 0x42, 0x70, 0x21, 0x0f, 0x31, 0xd2,
+		][..]);
+	}
+
+	#[test]
+	fn test_prehash_leaf_of() {
+		let short = Prehash::leaf_of(&[1, 2, 3]);
+		assert_eq!(&short[..3], &[1, 2, 3]);
+		assert!(short[3..].iter().all(|b| *b == 0));
+
+		let long = [0xabu8; 48];
+		let leaf = Prehash::leaf_of(&long[..]);
+		assert_eq!(leaf, Sha256::digest(&long[..]));
+
+		let mut db = InMemoryBackend::<Prehash>::default();
+		let vec = OwnedVector::create_with(&mut db, 2, None, |i| {
+			if i == 0 { short } else { leaf }
+		}).unwrap();
+		assert_eq!(vec.root(), Prehash::intermediate_of(&short, &leaf));
+		assert_ne!(vec.root(), Prehash::intermediate_of(&short, &Prehash::leaf_of(&long[..32])));
+
+		let empty = OwnedVector::<Prehash>::create(&mut db, 4, None).unwrap();
+		let mut inner_db = InMemoryBackend::<InheritedDigestConstruct<Sha256>>::default();
+		let inner_empty = OwnedVector::<InheritedDigestConstruct<Sha256>>::create(&mut inner_db, 4, None).unwrap();
+		assert_eq!(empty.root(), inner_empty.root());
+	}
+}
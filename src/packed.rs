@@ -1,35 +1,13 @@
 use generic_array::{GenericArray, ArrayLength};
-use core::ops::Range;
-use core::cmp;
 use core::marker::PhantomData;
+use core::ops::Range;
 use alloc::vec::Vec;
 
 use crate::length::LengthMixed;
 use crate::vector::Vector;
 use crate::raw::Raw;
 use crate::traits::{Construct, ReadBackend, WriteBackend, RootStatus, Owned, Dangling, Leak, Tree, Sequence, Error};
-use crate::utils::{host_len, host_max_len};
-
-fn coverings<Host: ArrayLength<u8>, Value: ArrayLength<u8>>(value_index: usize) -> (usize, Vec<Range<usize>>) {
-	let host_len = Host::to_usize();
-	let value_len = Value::to_usize();
-
-	let bytes = value_len * value_index;
-	let host_index = bytes / host_len;
-	let offset = bytes - host_len * host_index;
-
-	let mut ranges = Vec::new();
-	ranges.push(offset..cmp::min(offset + value_len, host_len));
-	let mut covered = cmp::min(offset + value_len, host_len) - offset;
-
-	while covered < value_len {
-		let rest = value_len - covered;
-		ranges.push(0..cmp::min(rest, host_len));
-		covered += cmp::min(rest, host_len);
-	}
-
-	(host_index, ranges)
-}
+use crate::utils::{host_len, host_max_len, coverings};
 
 /// `PackedVector` with owned root.
 pub type OwnedPackedVector<C, T, H, V> = PackedVector<Owned, C, T, H, V>;
@@ -64,6 +42,35 @@ impl<R: RootStatus, C: Construct, T, H: ArrayLength<u8>, V: ArrayLength<u8>> Pac
 		Ok(ret.into())
 	}
 
+	/// Get values in the given index range, reading each host cell once.
+	pub fn get_range<DB: ReadBackend<Construct=C> + ?Sized>(&self, db: &mut DB, range: Range<usize>) -> Result<Vec<T>, Error<DB::Error>> {
+		if range.end > self.len {
+			return Err(Error::AccessOverflowed)
+		}
+
+		let mut ret = Vec::with_capacity(range.end.saturating_sub(range.start));
+		let mut cached: Option<(usize, C::Value)> = None;
+		for index in range {
+			let mut value = GenericArray::<u8, V>::default();
+			let (covering_base, covering_ranges) = coverings::<H, V>(index);
+
+			let mut value_offset = 0;
+			for (i, range) in covering_ranges.into_iter().enumerate() {
+				let host_index = covering_base + i;
+				let host_value = match cached {
+					Some((cached_index, ref cached_value)) if cached_index == host_index => cached_value,
+					_ => &cached.insert((host_index, self.tuple.get(db, host_index)?)).1,
+				};
+				value[value_offset..(value_offset + range.end - range.start)].copy_from_slice(&host_value.as_ref()[range.clone()]);
+				value_offset += range.end - range.start;
+			}
+
+			ret.push(value.into());
+		}
+
+		Ok(ret)
+	}
+
 	/// Set value at index.
 	pub fn set<DB: WriteBackend<Construct=C> + ?Sized>(&mut self, db: &mut DB, index: usize, value: T) -> Result<(), Error<DB::Error>> {
 		let value: GenericArray<u8, V> = value.into();
@@ -80,6 +87,45 @@ impl<R: RootStatus, C: Construct, T, H: ArrayLength<u8>, V: ArrayLength<u8>> Pac
 		Ok(())
 	}
 
+	/// Get all values as raw bytes, reading each host cell once. Trailing
+	/// padding of the last host cell is not included.
+	pub fn as_bytes<DB: ReadBackend<Construct=C> + ?Sized>(&self, db: &mut DB) -> Result<Vec<u8>, Error<DB::Error>> {
+		let mut ret = Vec::new();
+		for i in 0..self.tuple.len() {
+			let host_value = self.tuple.get(db, i)?;
+			ret.extend_from_slice(&host_value.as_ref()[..H::to_usize()]);
+		}
+		ret.truncate(self.len * V::to_usize());
+
+		Ok(ret)
+	}
+
+	/// Create the packed vector from leaked metadata, checking the host
+	/// vector as `Vector::checked_from_leaked` does, and that the padding
+	/// of the last host cell past the length is zero. Fails with
+	/// `CorruptedDatabase` otherwise.
+	pub fn checked_from_leaked<DB: ReadBackend<Construct=C> + ?Sized>(
+		metadata: <Self as Leak>::Metadata,
+		db: &mut DB,
+	) -> Result<Self, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		let ret = Self::from_leaked(metadata);
+		Vector::<R, C>::checked_from_leaked(ret.tuple.metadata(), db)?;
+
+		let used = (ret.len * V::to_usize()) % H::to_usize();
+		if used != 0 {
+			let index = ret.tuple.leaf_index(ret.tuple.len() - 1).ok_or(Error::CorruptedDatabase)?;
+			let last = Raw::<Dangling, C>::from_leaked(ret.tuple.root()).get_collapsed(db, index)?
+				.ok_or(Error::CorruptedDatabase)?;
+			if last.as_ref()[used..H::to_usize()].iter().any(|b| *b != 0) {
+				return Err(Error::CorruptedDatabase)
+			}
+		}
+
+		Ok(ret)
+	}
+
 	/// Push a new value to the tuple.
 	pub fn push<DB: WriteBackend<Construct=C> + ?Sized>(&mut self, db: &mut DB, value: T) -> Result<(), Error<DB::Error>> {
 		let index = self.len;
@@ -124,6 +170,17 @@ impl<R: RootStatus, C: Construct, T, H: ArrayLength<u8>, V: ArrayLength<u8>> Pac
 		Ok(Some(ret))
 	}
 
+	/// Depth of the host vector.
+	pub fn host_depth(&self) -> usize {
+		self.tuple.depth()
+	}
+
+	/// Number of values fitting in the host vector at its current depth,
+	/// or its maximum length if given.
+	pub fn value_capacity(&self) -> u64 {
+		self.max_len.unwrap_or(self.tuple.current_max_len() * H::to_u64() / V::to_u64())
+	}
+
 	/// Create a packed tuple from raw merkle tree.
 	pub fn from_raw(raw: Raw<R, C>, len: usize, max_len: Option<u64>) -> Self {
 		let host_max_len = max_len.map(|l| host_max_len::<H, V>(l));
@@ -392,6 +449,88 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_as_bytes() {
+		let mut db = InMemory::default();
+		let mut tuple = PackedVector::<Owned, _, GenericArray<u8, typenum::U1>, U32, typenum::U1>::create(&mut db, 0, None).unwrap();
+
+		for i in 0..100 {
+			tuple.push(&mut db, GenericArray::from([i as u8])).unwrap();
+		}
+
+		assert_eq!(tuple.as_bytes(&mut db).unwrap(), (0..100).map(|i| i as u8).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn test_get_range() {
+		let mut db = InMemory::default();
+		let mut tuple = PackedVector::<Owned, _, GenericArray<u8, typenum::U8>, U32, typenum::U8>::create(&mut db, 0, None).unwrap();
+
+		for i in 0..100 {
+			tuple.push(&mut db, GenericArray::from([i as u8, 0, 0, 0, 0, 0, 0, 1])).unwrap();
+		}
+
+		let values = tuple.get_range(&mut db, 0..100).unwrap();
+		assert_eq!(values, (0..100).map(|i| tuple.get(&mut db, i).unwrap()).collect::<Vec<_>>());
+		assert_eq!(tuple.get_range(&mut db, 30..35).unwrap(), values[30..35].to_vec());
+		assert!(tuple.get_range(&mut db, 40..40).unwrap().is_empty());
+		assert_eq!(tuple.get_range(&mut db, 90..101), Err(Error::AccessOverflowed));
+	}
+
+	#[test]
+	fn test_host_depth_value_capacity() {
+		type Packed = PackedVector<Owned, crate::InheritedDigestConstruct<Sha256, ListValue>, GenericArray<u8, typenum::U8>, U32, typenum::U8>;
+
+		let mut db = InMemory::default();
+		for &(len, depth, capacity) in &[(0, 0, 4), (1, 0, 4), (4, 0, 4), (5, 1, 8), (9, 2, 16), (33, 4, 64)] {
+			let tuple = Packed::create(&mut db, len, None).unwrap();
+			assert_eq!(tuple.host_depth(), depth);
+			assert_eq!(tuple.value_capacity(), capacity);
+		}
+
+		let tuple = Packed::create(&mut db, 100, Some(100)).unwrap();
+		assert_eq!(tuple.host_depth(), 5);
+		assert_eq!(tuple.value_capacity(), 100);
+
+		let tuple = Packed::create(&mut db, 101, Some(101)).unwrap();
+		assert_eq!(tuple.host_depth(), 5);
+		assert_eq!(tuple.value_capacity(), 101);
+	}
+
+	#[test]
+	fn test_checked_from_leaked() {
+		let mut db = InMemory::default();
+		let mut tuple = PackedVector::<Owned, _, GenericArray<u8, typenum::U8>, U32, typenum::U8>::create(&mut db, 0, None).unwrap();
+		for i in 1..=10 {
+			tuple.push(&mut db, GenericArray::from([i as u8, 0, 0, 0, 0, 0, 0, 1])).unwrap();
+		}
+		let (root, len, max_len) = tuple.metadata();
+
+		type Packed = PackedVector::<Dangling, crate::InheritedDigestConstruct<Sha256, ListValue>, GenericArray<u8, typenum::U8>, U32, typenum::U8>;
+		let loaded = Packed::checked_from_leaked((root.clone(), len, max_len), &mut db).unwrap();
+		assert_eq!(loaded.get(&mut db, 9).unwrap(), GenericArray::from([10, 0, 0, 0, 0, 0, 0, 1]));
+		assert_eq!(Packed::checked_from_leaked((root.clone(), 9, max_len), &mut db).err(), Some(Error::CorruptedDatabase));
+		assert_eq!(Packed::checked_from_leaked((root, 20, max_len), &mut db).err(), Some(Error::CorruptedDatabase));
+	}
+
+	#[test]
+	fn test_checked_from_leaked_unit() {
+		type Unit = crate::UnitDigestConstruct<Sha256, ListValue>;
+
+		let mut db = crate::memory::InMemoryBackend::<Unit>::default();
+		let mut tuple = PackedVector::<Owned, Unit, GenericArray<u8, typenum::U8>, U32, typenum::U8>::create(&mut db, 0, None).unwrap();
+		for i in 0..9 {
+			let value = if i < 4 { i as u8 + 1 } else { 0 };
+			tuple.push(&mut db, GenericArray::from([value, 0, 0, 0, 0, 0, 0, 0])).unwrap();
+		}
+		let (root, len, max_len) = tuple.metadata();
+
+		type Packed = PackedVector::<Dangling, Unit, GenericArray<u8, typenum::U8>, U32, typenum::U8>;
+		let loaded = Packed::checked_from_leaked((root.clone(), len, max_len), &mut db).unwrap();
+		assert_eq!(loaded.get(&mut db, 3).unwrap(), GenericArray::from([4, 0, 0, 0, 0, 0, 0, 0]));
+		assert_eq!(loaded.get(&mut db, 8).unwrap(), GenericArray::default());
+	}
+
 	#[test]
 	fn test_vec() {
 		let mut db = InMemory::default();
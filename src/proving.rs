@@ -1,8 +1,11 @@
-use crate::{Backend, ReadBackend, WriteBackend, Construct, Index, IndexRoute, IndexSelection};
+use crate::{Backend, ReadBackend, WriteBackend, Construct, Index, IndexRoute, IndexSelection,
+			Raw, RootStatus, Error, BatchItem};
 use core::hash::Hash;
 use core::ops::Deref;
 use core::fmt;
 use alloc::boxed::Box;
+use alloc::vec::Vec;
+use alloc::collections::{BTreeMap, BTreeSet};
 #[cfg(feature = "std")]
 use std::collections::{HashMap as Map, HashSet as Set};
 #[cfg(not(feature = "std"))]
@@ -60,6 +63,19 @@ impl<'a, DB: Backend + ?Sized> ProvingBackend<'a, DB> where
 	pub fn into_state(self) -> ProvingState<<DB::Construct as Construct>::Value> {
 		self.state
 	}
+
+	/// Reset the proving state, returning proofs gathered since creation
+	/// or the last reset. Recorded inserts are cleared as well, so that
+	/// nodes written before the reset are proven when read afterwards.
+	pub fn reset(&mut self) -> Proofs<<DB::Construct as Construct>::Value> {
+		core::mem::take(&mut self.state).into()
+	}
+
+	/// Collect the gathered proofs and compact them against the given
+	/// root. Equivalent to `Proofs::from(backend).into_compact(root)`.
+	pub fn prove_into_compact(self, root: <DB::Construct as Construct>::Value) -> CompactValue<<DB::Construct as Construct>::Value> {
+		Proofs::from(self).into_compact(root)
+	}
 }
 
 impl<'a, DB: Backend + ?Sized> From<ProvingBackend<'a, DB>> for Proofs<<DB::Construct as Construct>::Value> where
@@ -93,6 +109,13 @@ impl<'a, DB: ReadBackend + ?Sized> ReadBackend for ProvingBackend<'a, DB> where
 		}
 		Ok(Some(value))
 	}
+
+	fn prefetch(
+		&mut self,
+		keys: &[<DB::Construct as Construct>::Value],
+	) {
+		self.db.prefetch(keys)
+	}
 }
 
 impl<'a, DB: WriteBackend + ?Sized> WriteBackend for ProvingBackend<'a, DB> where
@@ -114,6 +137,63 @@ impl<'a, DB: WriteBackend + ?Sized> WriteBackend for ProvingBackend<'a, DB> wher
 		self.state.inserts.insert(key.clone());
 		self.db.insert(key, value)
 	}
+
+	fn insert_batch(
+		&mut self,
+		items: &mut dyn Iterator<Item=BatchItem<DB::Construct>>,
+	) -> Result<(), Self::Error> {
+		let inserts = &mut self.state.inserts;
+		self.db.insert_batch(&mut items.map(|(key, value)| {
+			inserts.insert(key.clone());
+			(key, value)
+		}))
+	}
+}
+
+/// Read-only backend over a compact value. Intermediates are only
+/// computed on first access.
+pub struct CompactBackend<'a, C: Construct> where
+	C::Value: Eq + Hash + Ord,
+{
+	compact: &'a CompactValue<C::Value>,
+	proofs: Option<Proofs<C::Value>>,
+}
+
+impl<'a, C: Construct> CompactBackend<'a, C> where
+	C::Value: Eq + Hash + Ord,
+{
+	/// Create a new compact backend.
+	pub fn new(compact: &'a CompactValue<C::Value>) -> Self {
+		Self { compact, proofs: None }
+	}
+
+	/// Root of the compact value.
+	pub fn root(&self) -> C::Value {
+		self.compact.clone().root::<C>()
+	}
+}
+
+impl<'a, C: Construct> Backend for CompactBackend<'a, C> where
+	C::Value: Eq + Hash + Ord,
+{
+	type Construct = C;
+	type Error = ();
+}
+
+impl<'a, C: Construct> ReadBackend for CompactBackend<'a, C> where
+	C::Value: Eq + Hash + Ord,
+{
+	fn get(
+		&mut self,
+		key: &C::Value,
+	) -> Result<Option<(C::Value, C::Value)>, Self::Error> {
+		let compact = self.compact;
+		let proofs = self.proofs.get_or_insert_with(|| {
+			Proofs::from_compact::<C>(compact.clone()).0
+		});
+
+		Ok(proofs.0.get(key).cloned())
+	}
 }
 
 /// Type of proofs.
@@ -184,7 +264,7 @@ impl<V: Eq + Hash + Ord + Clone + Default> Proofs<V> {
 }
 
 /// Compact proofs.
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "parity-codec", derive(parity_codec::Encode, parity_codec::Decode))]
 pub enum CompactValue<V> {
@@ -194,6 +274,9 @@ pub enum CompactValue<V> {
 	Combined(Box<(CompactValue<V>, CompactValue<V>)>),
 }
 
+/// A proven value together with its compact proof.
+pub type ElementProof<V> = (V, CompactValue<V>);
+
 impl<V: Default> Default for CompactValue<V> {
 	fn default() -> Self {
 		CompactValue::Single(Default::default())
@@ -277,3 +360,155 @@ impl<V: Default + Clone> CompactValue<V> {
 		}
 	}
 }
+
+/// Verify a plain merkle proof of `leaf` at `index` against `root`. Proofs
+/// are sibling values ordered from the leaf up to the root.
+pub fn verify_proof<C: Construct, I: IntoIterator<Item=C::Value>>(
+	root: &C::Value,
+	leaf: C::Value,
+	proofs: I,
+	index: Index,
+) -> bool where
+	C::Value: PartialEq,
+{
+	match CompactValue::from_plain(leaf, proofs, index) {
+		Some(compact) => &compact.root::<C>() == root,
+		None => false,
+	}
+}
+
+/// Verify a plain merkle proof of exactly `D` siblings without allocating.
+/// Siblings are ordered from the leaf up to the root, and `index` must be
+/// at depth `D`.
+pub fn verify_proof_fixed<C: Construct, const D: usize>(
+	root: &C::Value,
+	leaf: C::Value,
+	index: Index,
+	siblings: [C::Value; D],
+) -> bool where
+	C::Value: PartialEq,
+{
+	let mut current = leaf;
+	let mut index = index;
+
+	for sibling in siblings.iter() {
+		let parent = match index.parent() {
+			Some(parent) => parent,
+			None => return false,
+		};
+		current = if parent.left() == index {
+			C::intermediate_of(&current, sibling)
+		} else {
+			C::intermediate_of(sibling, &current)
+		};
+		index = parent;
+	}
+
+	index == Index::root() && &current == root
+}
+
+/// Multiproof of a set of indices, containing only the minimal set of
+/// helper nodes needed to recompute the root.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MultiProof<V> {
+	/// Queried indices and their values.
+	pub leaves: Vec<(Index, V)>,
+	/// Helper indices and their values, in descending index order.
+	pub helpers: Vec<(Index, V)>,
+}
+
+impl<V: Clone + PartialEq> MultiProof<V> {
+	/// Verify the multiproof against `root`.
+	pub fn verify<C: Construct<Value=V>>(&self, root: &V) -> bool {
+		let mut known = BTreeMap::new();
+		for (index, value) in self.leaves.iter().chain(self.helpers.iter()) {
+			if let Some(old) = known.insert(*index, value.clone()) {
+				if &old != value {
+					return false
+				}
+			}
+		}
+
+		loop {
+			let index = match known.keys().next_back() {
+				Some(index) => *index,
+				None => return false,
+			};
+			let parent = match index.parent() {
+				Some(parent) => parent,
+				None => return known.get(&index) == Some(root),
+			};
+
+			let (left, right) = match (known.remove(&parent.left()), known.remove(&parent.right())) {
+				(Some(left), Some(right)) => (left, right),
+				_ => return false,
+			};
+			let value = C::intermediate_of(&left, &right);
+			if let Some(old) = known.insert(parent, value.clone()) {
+				if old != value {
+					return false
+				}
+			}
+		}
+	}
+}
+
+/// Prover of minimal multiproofs for a fixed set of indices. Unlike
+/// `ProvingBackend`, which records every node read, only siblings that
+/// cannot be computed from other queried nodes are included.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct QueryProver {
+	indices: Vec<Index>,
+}
+
+impl QueryProver {
+	/// Create a new prover for the given indices.
+	pub fn new(indices: Vec<Index>) -> Self {
+		Self { indices }
+	}
+
+	/// Queried indices.
+	pub fn indices(&self) -> &[Index] {
+		&self.indices
+	}
+
+	/// Indices of helper nodes needed to prove all queried indices, in
+	/// descending order.
+	pub fn helper_indices(&self) -> Vec<Index> {
+		let mut paths = BTreeSet::new();
+		let mut branches = BTreeSet::new();
+
+		for index in &self.indices {
+			let mut current = *index;
+			paths.insert(current);
+			while let Some(parent) = current.parent() {
+				branches.insert(if parent.left() == current { parent.right() } else { parent.left() });
+				paths.insert(parent);
+				current = parent;
+			}
+		}
+
+		branches.into_iter().rev().filter(|index| !paths.contains(index)).collect()
+	}
+
+	/// Create the multiproof of queried indices against `raw`.
+	pub fn prove<R: RootStatus, C: Construct, DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		raw: &Raw<R, C>,
+		db: &mut DB,
+	) -> Result<MultiProof<C::Value>, Error<DB::Error>> {
+		let helper_indices = self.helper_indices();
+		let mut indices = self.indices.clone();
+		indices.extend(helper_indices.iter().cloned());
+
+		let mut values = raw.get_many(db, &indices)?.into_iter()
+			.map(|value| value.ok_or(Error::CorruptedDatabase))
+			.collect::<Result<Vec<_>, _>>()?;
+		let helper_values = values.split_off(self.indices.len());
+
+		Ok(MultiProof {
+			leaves: self.indices.iter().cloned().zip(values).collect(),
+			helpers: helper_indices.into_iter().zip(helper_values).collect(),
+		})
+	}
+}
@@ -1,5 +1,6 @@
 use core::marker::PhantomData;
 use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
 
 use crate::index::{Index, IndexSelection, IndexRoute};
 use crate::traits::{Construct, ReadBackend, WriteBackend,
@@ -20,7 +21,7 @@ pub struct Raw<R: RootStatus, C: Construct> {
 impl<R: RootStatus, C: Construct> Default for Raw<R, C> {
 	fn default() -> Self {
 		Self {
-			root: Default::default(),
+			root: C::empty_end(),
 			_marker: PhantomData,
 		}
 	}
@@ -95,6 +96,234 @@ impl<R: RootStatus, C: Construct> Raw<R, C> {
 		}
 	}
 
+	/// Get value from the tree via generalized merkle index, checking every
+	/// node read with `Construct::verify_intermediate`. Fails with
+	/// `CorruptedDatabase` if the backend serves an inconsistent node.
+	pub fn get_verified<DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+		index: Index
+	) -> Result<Option<C::Value>, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		match index.route() {
+			IndexRoute::Root => Ok(Some(self.root.clone())),
+			IndexRoute::Select(selections) => {
+				let mut current = self.root.clone();
+
+				for selection in selections {
+					let pair = match db.get(&current)? {
+						Some(pair) => pair,
+						None => return Ok(None),
+					};
+					if !C::verify_intermediate(&current, &pair.0, &pair.1) {
+						return Err(Error::CorruptedDatabase)
+					}
+					current = match selection {
+						IndexSelection::Left => pair.0.clone(),
+						IndexSelection::Right => pair.1.clone(),
+					};
+				}
+
+				Ok(Some(current))
+			},
+		}
+	}
+
+	/// Get values from the tree via generalized merkle indices, in the
+	/// given order. Shared ancestors are only read from the backend once.
+	pub fn get_many<DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+		indices: &[Index],
+	) -> Result<Vec<Option<C::Value>>, Error<DB::Error>> {
+		let mut known = BTreeMap::new();
+		known.insert(Index::root(), self.root.clone());
+
+		let mut ret = Vec::with_capacity(indices.len());
+		for index in indices {
+			let selections = match index.route() {
+				IndexRoute::Root => Vec::new(),
+				IndexRoute::Select(selections) => selections,
+			};
+
+			let mut current_index = Index::root();
+			let mut current = Some(self.root.clone());
+			for selection in selections {
+				let value = match current {
+					Some(value) => value,
+					None => break,
+				};
+				let (left, right) = (current_index.left(), current_index.right());
+
+				if !known.contains_key(&left) {
+					if let Some((left_value, right_value)) = db.get(&value)? {
+						known.insert(left, left_value);
+						known.insert(right, right_value);
+					}
+				}
+
+				current_index = match selection {
+					IndexSelection::Left => left,
+					IndexSelection::Right => right,
+				};
+				current = known.get(&current_index).cloned();
+			}
+
+			ret.push(current);
+		}
+
+		Ok(ret)
+	}
+
+	/// Get value from the tree via generalized merkle index. Unlike `get`,
+	/// a walk stopping at `empty_end` returns `empty_end`, as constructs
+	/// that collapse empty subtrees into the default value store no nodes
+	/// below it.
+	pub fn get_collapsed<DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+		index: Index
+	) -> Result<Option<C::Value>, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		let selections = match index.route() {
+			IndexRoute::Root => return Ok(Some(self.root.clone())),
+			IndexRoute::Select(selections) => selections,
+		};
+
+		let mut current = self.root.clone();
+		for selection in selections {
+			current = match db.get(&current)? {
+				Some((left, right)) => match selection {
+					IndexSelection::Left => left,
+					IndexSelection::Right => right,
+				},
+				None if current == C::empty_end() => return Ok(Some(current)),
+				None => return Ok(None),
+			};
+		}
+
+		Ok(Some(current))
+	}
+
+	/// Get value from the tree via generalized merkle index, failing with
+	/// `InvalidParameter` if the index is deeper than `max_depth`. Use this
+	/// for indices from untrusted sources.
+	pub fn get_bounded<DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+		index: Index,
+		max_depth: usize,
+	) -> Result<Option<C::Value>, Error<DB::Error>> {
+		let depth = index.route_len();
+		if depth > max_depth {
+			return Err(Error::InvalidParameter)
+		}
+
+		self.get(db, index)
+	}
+
+	/// Get value from the tree of the given depth via generalized merkle
+	/// index. Unlike `get`, when descending into an absent subtree, the
+	/// canonical empty value at the index's depth is returned instead of
+	/// `None`. The walk must stop at an empty value, either `empty_end` or
+	/// the empty value of that node's depth. Stopping at any other end
+	/// value fails with `CorruptedDatabase`.
+	pub fn get_with_empty<DB: WriteBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+		index: Index,
+		depth: usize,
+	) -> Result<C::Value, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		let index_depth = index.route_len();
+		if index_depth > depth {
+			return Err(Error::InvalidParameter)
+		}
+
+		let selections = match index.route() {
+			IndexRoute::Root => return Ok(self.root.clone()),
+			IndexRoute::Select(selections) => selections,
+		};
+
+		let mut current = self.root.clone();
+		for (current_depth, selection) in selections.into_iter().enumerate() {
+			current = match db.get(&current)? {
+				Some((left, right)) => match selection {
+					IndexSelection::Left => left,
+					IndexSelection::Right => right,
+				},
+				None => {
+					if current != C::empty_end() && current != C::empty_at(db, depth - current_depth)? {
+						return Err(Error::CorruptedDatabase)
+					}
+
+					return Ok(C::empty_at(db, depth - index_depth)?)
+				},
+			};
+		}
+
+		Ok(current)
+	}
+
+	/// Whether the root is the depth-zero empty value.
+	pub fn root_is_empty(&self) -> bool where
+		C::Value: PartialEq,
+	{
+		self.root == C::empty_end()
+	}
+
+	/// Get the height of the tree, by walking the leftmost spine until an
+	/// end node is reached. Returns the number of intermediate levels.
+	pub fn height<DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+	) -> Result<usize, Error<DB::Error>> {
+		let mut current = self.root.clone();
+		let mut height = 0;
+
+		while let Some((left, _)) = db.get(&current)? {
+			current = left;
+			height += 1;
+		}
+
+		Ok(height)
+	}
+
+	/// Get indices of the topmost differing nodes between this tree and
+	/// another tree, in left-to-right order. Equal intermediates are
+	/// skipped without descending. A differing node is reported when
+	/// either side of it is not an intermediate.
+	pub fn diff<R2: RootStatus, DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		other: &Raw<R2, C>,
+		db: &mut DB,
+	) -> Result<Vec<Index>, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		let mut ret = Vec::new();
+		let mut pending = Vec::new();
+		pending.push((Index::root(), self.root.clone(), other.root.clone()));
+
+		while let Some((index, left, right)) = pending.pop() {
+			if left == right {
+				continue
+			}
+
+			match (db.get(&left)?, db.get(&right)?) {
+				(Some(left_pair), Some(right_pair)) => {
+					pending.push((index.right(), left_pair.1, right_pair.1));
+					pending.push((index.left(), left_pair.0, right_pair.0));
+				},
+				_ => ret.push(index),
+			}
+		}
+
+		Ok(ret)
+	}
+
 	/// Set value of the merkle tree via generalized merkle index.
 	pub fn set<DB: WriteBackend<Construct=C> + ?Sized>(
 		&mut self,
@@ -158,6 +387,114 @@ impl<R: RootStatus, C: Construct> Raw<R, C> {
 		}
 
 
+		if R::is_owned() {
+			db.rootify(&update)?;
+			db.unrootify(&self.root)?;
+		}
+
+		self.root = update;
+		Ok(())
+	}
+
+	/// Graft an intermediate subtree whose nodes are already in the backend
+	/// at the given index. The new parent node references `subtree_root`,
+	/// so the subtree stays alive when other trees sharing it are dropped.
+	/// Fails with `InvalidParameter` if `subtree_root` is not stored in the
+	/// backend. Use `set` for leaf values.
+	pub fn graft<DB: WriteBackend<Construct=C> + ?Sized>(
+		&mut self,
+		db: &mut DB,
+		index: Index,
+		subtree_root: C::Value,
+	) -> Result<(), Error<DB::Error>> {
+		if db.get(&subtree_root)?.is_none() {
+			return Err(Error::InvalidParameter)
+		}
+
+		self.set(db, index, subtree_root)
+	}
+
+	/// Set multiple values of the merkle tree via generalized merkle
+	/// indices, given in ascending order. Intermediate values shared by
+	/// the paths are only read and hashed once. No index can be an
+	/// ancestor of another.
+	pub fn set_batch<DB: WriteBackend<Construct=C> + ?Sized>(
+		&mut self,
+		db: &mut DB,
+		items: &[(Index, C::Value)],
+	) -> Result<(), Error<DB::Error>> {
+		if items.is_empty() {
+			return Ok(())
+		}
+		if items.windows(2).any(|w| w[0].0 >= w[1].0) {
+			return Err(Error::InvalidParameter)
+		}
+
+		let mut pairs = BTreeMap::<Index, (C::Value, C::Value)>::new();
+		for (index, _) in items {
+			let mut ancestors = Vec::new();
+			let mut current = index.parent();
+			while let Some(parent) = current {
+				if items.binary_search_by(|item| item.0.cmp(&parent)).is_ok() {
+					return Err(Error::InvalidParameter)
+				}
+				ancestors.push(parent);
+				current = parent.parent();
+			}
+
+			let mut value = Some(self.root.clone());
+			for (i, ancestor) in ancestors.iter().rev().enumerate() {
+				if !pairs.contains_key(ancestor) {
+					let pair = match &value {
+						Some(value) => db.get(value)?.unwrap_or_default(),
+						None => Default::default(),
+					};
+					pairs.insert(*ancestor, pair);
+				}
+
+				let pair = &pairs[ancestor];
+				let child = if i + 1 < ancestors.len() {
+					ancestors[ancestors.len() - i - 2]
+				} else {
+					*index
+				};
+				value = Some(if ancestor.left() == child {
+					pair.0.clone()
+				} else {
+					pair.1.clone()
+				});
+			}
+		}
+
+		let mut dirty = items.iter()
+			.map(|(index, value)| (*index, Some(value.clone())))
+			.collect::<BTreeMap<_, _>>();
+		let update = loop {
+			let index = *dirty.keys().next_back().expect("dirty is only empty after root is reached; qed");
+			let value = match dirty.remove(&index).expect("index is fetched from dirty keys; qed") {
+				Some(value) => value,
+				None => {
+					let pair = pairs[&index].clone();
+					let intermediate = C::intermediate_of(&pair.0, &pair.1);
+					db.insert(intermediate.clone(), pair)?;
+					intermediate
+				},
+			};
+
+			match index.parent() {
+				None => break value,
+				Some(parent) => {
+					let pair = pairs.get_mut(&parent).expect("all ancestors are read; qed");
+					if parent.left() == index {
+						pair.0 = value;
+					} else {
+						pair.1 = value;
+					}
+					dirty.insert(parent, None);
+				},
+			}
+		};
+
 		if R::is_owned() {
 			db.rootify(&update)?;
 			db.unrootify(&self.root)?;
@@ -199,6 +536,7 @@ mod tests {
 	use crate::traits::Owned;
 	use generic_array::{arr, arr_impl};
 	use sha2::Sha256;
+	use core::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 	type Construct = crate::InheritedDigestConstruct<Sha256>;
 	type InMemory = crate::memory::InMemoryBackend<Construct>;
@@ -287,6 +625,91 @@ mod tests {
 		}
 	}
 
+	static HASHES: AtomicUsize = AtomicUsize::new(0);
+
+	struct CountingConstruct;
+
+	impl crate::Construct for CountingConstruct {
+		type Value = <Construct as crate::Construct>::Value;
+
+		fn intermediate_of(left: &Self::Value, right: &Self::Value) -> Self::Value {
+			HASHES.fetch_add(1, AtomicOrdering::SeqCst);
+			Construct::intermediate_of(left, right)
+		}
+
+		fn empty_at<DB: WriteBackend<Construct=Self> + ?Sized>(
+			db: &mut DB,
+			depth_to_bottom: usize
+		) -> Result<Self::Value, DB::Error> {
+			let mut current = Self::Value::default();
+			for _ in 0..depth_to_bottom {
+				let value = (current, current);
+				let key = Self::intermediate_of(&value.0, &value.1);
+				db.insert(key, value)?;
+				current = key;
+			}
+			Ok(current)
+		}
+	}
+
+	#[test]
+	fn test_graft() {
+		let mut db = InMemory::default();
+		let mut source = Raw::<Owned, Construct>::default();
+		for i in 4..8 {
+			source.set(&mut db, Index::from_one(i).unwrap(), sinarr!(i as u8)).unwrap();
+		}
+		let subtree = source.get(&mut db, Index::from_one(3).unwrap()).unwrap().unwrap();
+
+		let mut target = Raw::<Owned, Construct>::default();
+		target.set(&mut db, Index::from_one(2).unwrap(), sinarr!(1)).unwrap();
+		assert_eq!(target.graft(&mut db, Index::from_one(3).unwrap(), sinarr!(9)), Err(Error::InvalidParameter));
+		target.graft(&mut db, Index::from_one(3).unwrap(), subtree).unwrap();
+
+		source.drop(&mut db).unwrap();
+		assert_eq!(db.verify_integrity(), Ok(()));
+		assert_eq!(target.get(&mut db, Index::from_one(6).unwrap()).unwrap(), Some(sinarr!(6)));
+		assert_eq!(target.get(&mut db, Index::from_one(7).unwrap()).unwrap(), Some(sinarr!(7)));
+
+		target.drop(&mut db).unwrap();
+		assert_eq!(db.as_ref(), InMemory::default().as_ref());
+	}
+
+	#[test]
+	fn test_set_batch() {
+		let mut db1 = crate::memory::InMemoryBackend::<CountingConstruct>::default();
+		let mut db2 = crate::memory::InMemoryBackend::<CountingConstruct>::default();
+		let mut list1 = Raw::<Owned, CountingConstruct>::default();
+		let mut list2 = Raw::<Owned, CountingConstruct>::default();
+
+		let before = HASHES.load(AtomicOrdering::SeqCst);
+		for i in 32..64 {
+			list1.set(&mut db1, Index::from_one(i).unwrap(), sinarr!(i as u8)).unwrap();
+		}
+		let single = HASHES.load(AtomicOrdering::SeqCst) - before;
+
+		let items = (32..64)
+			.map(|i| (Index::from_one(i).unwrap(), sinarr!(i as u8)))
+			.collect::<Vec<_>>();
+		let before = HASHES.load(AtomicOrdering::SeqCst);
+		list2.set_batch(&mut db2, &items).unwrap();
+		let batched = HASHES.load(AtomicOrdering::SeqCst) - before;
+
+		assert_eq!(list1.root(), list2.root());
+		assert_eq!(db1.as_ref(), db2.as_ref());
+		assert_eq!(single, 32 * 5);
+		assert_eq!(batched, 31);
+
+		assert_eq!(list2.set_batch(&mut db2, &[
+			(Index::from_one(5).unwrap(), sinarr!(1)),
+			(Index::from_one(4).unwrap(), sinarr!(2)),
+		]), Err(Error::InvalidParameter));
+		assert_eq!(list2.set_batch(&mut db2, &[
+			(Index::from_one(2).unwrap(), sinarr!(1)),
+			(Index::from_one(4).unwrap(), sinarr!(2)),
+		]), Err(Error::InvalidParameter));
+	}
+
 	#[test]
 	fn test_set_only() {
 		let mut db1 = InMemory::default();
@@ -312,6 +735,143 @@ mod tests {
 		assert_eq!(db1.as_ref().len(), 2);
 	}
 
+	#[test]
+	fn test_get_bounded() {
+		let mut db = InMemory::default();
+		let mut list = Raw::<Owned, Construct>::default();
+		for i in 32..64 {
+			list.set(&mut db, Index::from_one(i).unwrap(), sinarr!(i as u8)).unwrap();
+		}
+
+		assert_eq!(list.get_bounded(&mut db, Index::from_one(45).unwrap(), 5).unwrap(), Some(sinarr!(45)));
+		assert_eq!(list.get_bounded(&mut db, Index::root(), 0).unwrap(), Some(list.root()));
+		assert_eq!(list.get_bounded(&mut db, Index::from_one(45).unwrap(), 4), Err(Error::InvalidParameter));
+		assert_eq!(list.get_bounded(&mut db, Index::from_one(1 << 40).unwrap(), 16), Err(Error::InvalidParameter));
+	}
+
+	#[test]
+	fn test_get_many() {
+		let mut db = InMemory::default();
+		let mut list = Raw::<Owned, Construct>::default();
+		for i in 32..64 {
+			list.set(&mut db, Index::from_one(i).unwrap(), sinarr!(i as u8)).unwrap();
+		}
+
+		let indices = [45, 1, 32, 11, 45, 130].iter()
+			.map(|i| Index::from_one(*i).unwrap())
+			.collect::<Vec<_>>();
+		let values = list.get_many(&mut db, &indices).unwrap();
+		for (index, value) in indices.into_iter().zip(values) {
+			assert_eq!(value, list.get(&mut db, index).unwrap());
+		}
+	}
+
+	#[test]
+	fn test_get_with_empty() {
+		let mut db = InMemory::default();
+		let raw = Raw::<Owned, Construct>::default();
+
+		for (index, index_depth) in [(2, 1), (5, 2), (13, 3)].iter() {
+			let index = Index::from_one(*index).unwrap();
+			assert_eq!(raw.get(&mut db, index).unwrap(), None);
+			assert_eq!(
+				raw.get_with_empty(&mut db, index, 3).unwrap(),
+				<Construct as crate::Construct>::empty_at(&mut db, 3 - index_depth).unwrap(),
+			);
+		}
+		assert_eq!(raw.get_with_empty(&mut db, Index::from_one(16).unwrap(), 3), Err(Error::InvalidParameter));
+
+		let mut raw = Raw::<Owned, Construct>::default();
+		raw.set(&mut db, Index::from_one(4).unwrap(), sinarr!(4)).unwrap();
+		assert_eq!(raw.get_with_empty(&mut db, Index::from_one(4).unwrap(), 2).unwrap(), sinarr!(4));
+		assert_eq!(raw.get_with_empty(&mut db, Index::from_one(8).unwrap(), 3), Err(Error::CorruptedDatabase));
+		assert_eq!(raw.get_with_empty(&mut db, Index::from_one(6).unwrap(), 3).unwrap(),
+				   <Construct as crate::Construct>::empty_at(&mut db, 1).unwrap());
+
+		let mut vec = crate::OwnedVector::<Construct>::create(&mut db, 4, Some(4)).unwrap();
+		vec.set(&mut db, 0, sinarr!(1)).unwrap();
+		let raw = Raw::<Dangling, Construct>::from_leaked(vec.root());
+		assert_eq!(raw.get_with_empty(&mut db, Index::from_one(7).unwrap(), 2).unwrap(), Default::default());
+		assert_eq!(raw.get_with_empty(&mut db, Index::from_one(14).unwrap(), 3).unwrap(),
+				   <Construct as crate::Construct>::empty_at(&mut db, 0).unwrap());
+	}
+
+	#[test]
+	fn test_get_verified() {
+		let mut db = InMemory::default();
+		let mut raw = Raw::<Owned, Construct>::default();
+		raw.set(&mut db, Index::from_one(4).unwrap(), sinarr!(4)).unwrap();
+		raw.set(&mut db, Index::from_one(7).unwrap(), sinarr!(7)).unwrap();
+		assert_eq!(raw.get_verified(&mut db, Index::from_one(4).unwrap()).unwrap(), Some(sinarr!(4)));
+		assert_eq!(raw.get_verified(&mut db, Index::from_one(7).unwrap()).unwrap(), Some(sinarr!(7)));
+
+		let key = sinarr!(1);
+		db.insert(key, (sinarr!(2), sinarr!(3))).unwrap();
+		let forged = Raw::<Dangling, Construct>::from_leaked(key);
+		assert_eq!(forged.get(&mut db, Index::from_one(2).unwrap()).unwrap(), Some(sinarr!(2)));
+		assert_eq!(forged.get_verified(&mut db, Index::from_one(2).unwrap()), Err(Error::CorruptedDatabase));
+		assert_eq!(forged.get_verified(&mut db, Index::root()).unwrap(), Some(key));
+	}
+
+	#[test]
+	fn test_root_is_empty() {
+		let mut db = InMemory::default();
+		let mut raw = Raw::<Owned, Construct>::default();
+		assert!(raw.root_is_empty());
+		assert!(raw.is_empty_root());
+
+		raw.set(&mut db, Index::from_one(2).unwrap(), sinarr!(1)).unwrap();
+		assert!(!raw.root_is_empty());
+		raw.set(&mut db, Index::root(), Default::default()).unwrap();
+		assert!(raw.root_is_empty());
+
+		let mut vec = crate::OwnedVector::<Construct>::create(&mut db, 0, None).unwrap();
+		assert!(vec.is_empty_root());
+		vec.push(&mut db, sinarr!(1)).unwrap();
+		assert!(!vec.is_empty_root());
+		vec.pop(&mut db).unwrap();
+		assert!(vec.is_empty_root());
+
+		let vec = crate::OwnedVector::<Construct>::create(&mut db, 4, None).unwrap();
+		assert!(!vec.is_empty_root());
+	}
+
+	#[test]
+	fn test_height() {
+		let mut db = InMemory::default();
+		let empty = Raw::<Owned, Construct>::default();
+		assert_eq!(empty.height(&mut db).unwrap(), 0);
+
+		let values = (0..5u8).map(|i| sinarr!(i)).collect::<Vec<_>>();
+		let root = crate::utils::vector_tree(&values, &mut db, Some(16)).unwrap();
+		let raw = Raw::<Owned, Construct>::new(root);
+		assert_eq!(raw.height(&mut db).unwrap(), 4);
+
+		let root = crate::utils::vector_tree(&values, &mut db, None).unwrap();
+		let raw = Raw::<Owned, Construct>::new(root);
+		assert_eq!(raw.height(&mut db).unwrap(), 3);
+	}
+
+	#[test]
+	fn test_diff() {
+		let mut db = InMemory::default();
+		let mut list1 = Raw::<Owned, Construct>::default();
+		for i in 32..64 {
+			list1.set(&mut db, Index::from_one(i).unwrap(), sinarr!(i as u8)).unwrap();
+		}
+		let mut list2 = Raw::<Owned, Construct>::new(list1.root());
+		db.rootify(&list2.root()).unwrap();
+
+		assert_eq!(list1.diff(&list2, &mut db).unwrap(), Vec::new());
+
+		list2.set(&mut db, Index::from_one(45).unwrap(), sinarr!(0)).unwrap();
+		assert_eq!(list1.diff(&list2, &mut db).unwrap(), vec![Index::from_one(45).unwrap()]);
+
+		list2.set(&mut db, Index::from_one(33).unwrap(), sinarr!(0)).unwrap();
+		assert_eq!(list2.diff(&list1, &mut db).unwrap(),
+				   vec![Index::from_one(33).unwrap(), Index::from_one(45).unwrap()]);
+	}
+
 	#[test]
 	fn test_intermediate() {
 		let mut db = InMemory::default();
@@ -0,0 +1,159 @@
+use crate::traits::{Construct, WriteBackend, Tree, Error};
+
+/// Owned tree that is dropped from the backend when it goes out of
+/// scope. Because `Drop` cannot return errors, an error slot can be
+/// given to inspect any failure afterwards, or `finish` can be called to
+/// drop explicitly.
+///
+/// ```
+/// use bm::{OwnedList, InMemoryBackend, InheritedDigestConstruct, Scoped, Tree};
+/// use generic_array::GenericArray;
+/// use sha2::Sha256;
+///
+/// # #[derive(Clone, PartialEq, Eq, Debug, Default, Ord, PartialOrd, Hash)]
+/// # struct ListValue([u8; 32]);
+/// # impl AsRef<[u8]> for ListValue {
+/// #     fn as_ref(&self) -> &[u8] { &self.0[..] }
+/// # }
+/// # impl From<usize> for ListValue {
+/// #     fn from(value: usize) -> Self {
+/// #         let mut ret = [0u8; 32];
+/// #         ret[0..8].copy_from_slice(&(value as u64).to_le_bytes());
+/// #         ListValue(ret)
+/// #     }
+/// # }
+/// # impl From<ListValue> for usize {
+/// #     fn from(value: ListValue) -> usize {
+/// #         let mut raw = [0u8; 8];
+/// #         raw.copy_from_slice(&value.0[0..8]);
+/// #         u64::from_le_bytes(raw) as usize
+/// #     }
+/// # }
+/// # impl From<GenericArray<u8, typenum::U32>> for ListValue {
+/// #     fn from(array: GenericArray<u8, typenum::U32>) -> Self {
+/// #         let mut ret = [0u8; 32];
+/// #         ret.copy_from_slice(&array[..]);
+/// #         ListValue(ret)
+/// #     }
+/// # }
+/// type Construct = InheritedDigestConstruct<Sha256, ListValue>;
+///
+/// let mut db = InMemoryBackend::<Construct>::default();
+/// let mut error = None;
+/// {
+///     let list = OwnedList::<Construct>::create(&mut db, None).unwrap();
+///     let mut list = Scoped::with_error_slot(list, &mut db, &mut error);
+///     list.with_mut(|list, db| list.push(db, 1.into())).unwrap();
+///     assert_eq!(list.with(|list, db| list.get(db, 0)).unwrap(), 1.into());
+/// }
+/// assert_eq!(error, None);
+/// ```
+pub struct Scoped<'a, T: Tree, DB: WriteBackend<Construct=T::Construct> + ?Sized> {
+	tree: Option<T>,
+	db: &'a mut DB,
+	error: Option<&'a mut Option<Error<DB::Error>>>,
+}
+
+impl<'a, T: Tree, DB: WriteBackend<Construct=T::Construct> + ?Sized> Scoped<'a, T, DB> {
+	/// Create a new scoped tree. Errors on drop are discarded.
+	pub fn new(tree: T, db: &'a mut DB) -> Self {
+		Self { tree: Some(tree), db, error: None }
+	}
+
+	/// Create a new scoped tree, storing any error on drop into the
+	/// given slot.
+	pub fn with_error_slot(tree: T, db: &'a mut DB, error: &'a mut Option<Error<DB::Error>>) -> Self {
+		Self { tree: Some(tree), db, error: Some(error) }
+	}
+
+	/// Root of the scoped tree.
+	pub fn root(&self) -> <T::Construct as Construct>::Value {
+		self.tree().root()
+	}
+
+	/// Reference to the scoped tree.
+	pub fn tree(&self) -> &T {
+		self.tree.as_ref().expect("tree is only taken on finish or drop; qed")
+	}
+
+	/// Call with the scoped tree and the backend.
+	pub fn with<RT, F: FnOnce(&T, &mut DB) -> RT>(&mut self, f: F) -> RT {
+		let tree = self.tree.as_ref().expect("tree is only taken on finish or drop; qed");
+		f(tree, self.db)
+	}
+
+	/// Call with a mutable reference to the scoped tree and the backend.
+	pub fn with_mut<RT, F: FnOnce(&mut T, &mut DB) -> RT>(&mut self, f: F) -> RT {
+		let tree = self.tree.as_mut().expect("tree is only taken on finish or drop; qed");
+		f(tree, self.db)
+	}
+
+	/// Drop the tree from the backend, returning any error.
+	pub fn finish(mut self) -> Result<(), Error<DB::Error>> {
+		match self.tree.take() {
+			Some(tree) => tree.drop(self.db),
+			None => Ok(()),
+		}
+	}
+
+	/// Release the tree without dropping it from the backend.
+	pub fn into_inner(mut self) -> T {
+		self.tree.take().expect("tree is only taken on finish or drop; qed")
+	}
+}
+
+impl<'a, T: Tree, DB: WriteBackend<Construct=T::Construct> + ?Sized> Drop for Scoped<'a, T, DB> {
+	fn drop(&mut self) {
+		if let Some(tree) = self.tree.take() {
+			if let Err(err) = tree.drop(self.db) {
+				if let Some(slot) = self.error.as_mut() {
+					**slot = Some(err);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{OwnedVector, Raw, Owned, Index};
+	use generic_array::GenericArray;
+	use sha2::Sha256;
+
+	type Construct = crate::InheritedDigestConstruct<Sha256>;
+	type InMemory = crate::memory::InMemoryBackend<Construct>;
+
+	#[test]
+	fn test_scoped_drop() {
+		let mut db1 = InMemory::default();
+		let mut db2 = InMemory::default();
+
+		let mut vec = OwnedVector::<Construct>::create(&mut db1, 0, None).unwrap();
+		for i in 0..10u8 {
+			vec.push(&mut db1, GenericArray::from([i; 32])).unwrap();
+		}
+		vec.drop(&mut db1).unwrap();
+
+		let mut error = None;
+		{
+			let vec = OwnedVector::<Construct>::create(&mut db2, 0, None).unwrap();
+			let mut vec = Scoped::with_error_slot(vec, &mut db2, &mut error);
+			for i in 0..10u8 {
+				vec.with_mut(|vec, db| vec.push(db, GenericArray::from([i; 32]))).unwrap();
+			}
+			assert_eq!(vec.tree().len(), 10);
+		}
+		assert_eq!(error, None);
+		assert_eq!(db1.as_ref(), db2.as_ref());
+
+		let mut raw = Raw::<Owned, Construct>::default();
+		raw.set(&mut db2, Index::root().left(), GenericArray::from([1; 32])).unwrap();
+		let root = raw.root();
+		let raw = Scoped::new(raw, &mut db2).into_inner();
+		assert_eq!(raw.root(), root);
+		assert!(db2.as_ref().contains_key(&root));
+		assert_eq!(Scoped::new(raw, &mut db2).finish(), Ok(()));
+		assert!(!db2.as_ref().contains_key(&root));
+	}
+}
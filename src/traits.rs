@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 /// Construct for a merkle tree.
 pub trait Construct: Sized {
 	/// Value stored in this merkle database.
@@ -5,12 +7,38 @@ pub trait Construct: Sized {
 
 	/// Get the intermediate value of given left and right child.
 	fn intermediate_of(left: &Self::Value, right: &Self::Value) -> Self::Value;
+	/// Preprocess a basic leaf value before it is put into the tree.
+	/// Applied by `utils::leaf_vector_tree` to its input values. Sequences
+	/// store leaves as given. Defaults to identity.
+	fn hash_leaf(value: &Self::Value) -> Self::Value {
+		value.clone()
+	}
+	/// Whether the stored `(left, right)` pair hashes to `key`. Defaults to
+	/// recomputing `intermediate_of` and comparing.
+	fn verify_intermediate(key: &Self::Value, left: &Self::Value, right: &Self::Value) -> bool where
+		Self::Value: PartialEq,
+	{
+		&Self::intermediate_of(left, right) == key
+	}
+	/// The empty end value at depth zero.
+	fn empty_end() -> Self::Value {
+		Default::default()
+	}
 	/// Get or create the empty value given a backend. `empty_at(0)`
-	/// should always equal to `Value::End(Default::default())`.
+	/// should always equal to `empty_end()`.
 	fn empty_at<DB: WriteBackend<Construct=Self> + ?Sized>(
 		db: &mut DB,
 		depth_to_bottom: usize
 	) -> Result<Self::Value, DB::Error>;
+	/// Get or create all empty values from depth zero to `max_depth`,
+	/// inclusive. Item `i` equals `empty_at(i)`, so the result can be
+	/// cached for repeated fixed-size merkleization.
+	fn empty_chain<DB: WriteBackend<Construct=Self> + ?Sized>(
+		db: &mut DB,
+		max_depth: usize
+	) -> Result<Vec<Self::Value>, DB::Error> {
+		(0..=max_depth).map(|depth| Self::empty_at(db, depth)).collect()
+	}
 }
 
 /// Represents a basic merkle tree with a known root.
@@ -22,11 +50,41 @@ pub trait Tree {
 
 	/// Root of the merkle tree.
 	fn root(&self) -> <Self::Construct as Construct>::Value;
+	/// Whether the root is the depth-zero empty value, as it is for a tree
+	/// that was never set. Empty subtrees of larger depth are not
+	/// considered, since their roots are `empty_at` of that depth.
+	fn is_empty_root(&self) -> bool where
+		<Self::Construct as Construct>::Value: PartialEq,
+	{
+		self.root() == Self::Construct::empty_end()
+	}
 	/// Drop the merkle tree.
 	fn drop<DB: WriteBackend<Construct=Self::Construct> + ?Sized>(
 		self,
 		db: &mut DB
 	) -> Result<(), Error<DB::Error>>;
+	/// Consume the merkle tree without freeing any of its nodes. This
+	/// makes deliberate leaking explicit, for example when the tree was
+	/// only built to compute its root.
+	///
+	/// ```
+	/// use bm::{NoopBackend, OwnedVector, InheritedDigestConstruct, Tree};
+	/// use generic_array::GenericArray;
+	/// use sha2::Sha256;
+	///
+	/// type Construct = InheritedDigestConstruct<Sha256, GenericArray<u8, typenum::U32>>;
+	///
+	/// let mut db = NoopBackend::<Construct>::default();
+	/// let vector = OwnedVector::<Construct>::create_with(&mut db, 4, None, |i| {
+	///     GenericArray::from([i as u8; 32])
+	/// }).unwrap();
+	/// let root = vector.root();
+	/// vector.forget();
+	/// # let _ = root;
+	/// ```
+	fn forget(self) where
+		Self: Sized,
+	{ }
 	/// Convert the tree into a raw tree.
 	fn into_raw(self) -> crate::Raw<Self::RootStatus, Self::Construct>;
 }
@@ -35,6 +93,10 @@ pub trait Tree {
 pub trait Sequence: Tree {
 	/// The length of the tree.
 	fn len(&self) -> usize;
+	/// Whether the tree has no items.
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
 }
 
 /// Root status of a merkle tree.
@@ -93,8 +155,18 @@ pub trait ReadBackend: Backend {
 		&mut self,
 		key: &<Self::Construct as Construct>::Value,
 	) -> Result<Option<(<Self::Construct as Construct>::Value, <Self::Construct as Construct>::Value)>, Self::Error>;
+	/// Hint that the given keys are about to be read. Caching or disk
+	/// backends can override this to warm their cache. By default it
+	/// does nothing.
+	fn prefetch(
+		&mut self,
+		_keys: &[<Self::Construct as Construct>::Value],
+	) { }
 }
 
+/// Item of a write batch, a key with its left and right children.
+pub type BatchItem<C> = (<C as Construct>::Value, (<C as Construct>::Value, <C as Construct>::Value));
+
 /// Write backend.
 pub trait WriteBackend: ReadBackend {
 	/// Rootify a key.
@@ -114,6 +186,19 @@ pub trait WriteBackend: ReadBackend {
 		key: <Self::Construct as Construct>::Value,
 		value: (<Self::Construct as Construct>::Value, <Self::Construct as Construct>::Value)
 	) -> Result<(), Self::Error>;
+	/// Insert multiple internal items, in order. Backends with native
+	/// write batches can override this. By default it calls `insert`
+	/// for each item. The iterator is taken as a trait object so that
+	/// the backend stays object-safe.
+	fn insert_batch(
+		&mut self,
+		items: &mut dyn Iterator<Item=BatchItem<Self::Construct>>,
+	) -> Result<(), Self::Error> {
+		for (key, value) in items {
+			self.insert(key, value)?;
+		}
+		Ok(())
+	}
 }
 
 /// Dynamic backend, where error is stripped.
@@ -142,6 +227,13 @@ impl<Ba: ReadBackend> ReadBackend for DynBackend<Ba> {
 	) -> Result<Option<(<Self::Construct as Construct>::Value, <Self::Construct as Construct>::Value)>, Self::Error> {
 		self.0.get(key).map_err(|_| ())
 	}
+
+	fn prefetch(
+		&mut self,
+		keys: &[<Self::Construct as Construct>::Value],
+	) {
+		self.0.prefetch(keys)
+	}
 }
 
 impl<Ba: WriteBackend> WriteBackend for DynBackend<Ba> {
@@ -166,6 +258,13 @@ impl<Ba: WriteBackend> WriteBackend for DynBackend<Ba> {
 	) -> Result<(), Self::Error> {
 		self.0.insert(key, value).map_err(|_| ())
 	}
+
+	fn insert_batch(
+		&mut self,
+		items: &mut dyn Iterator<Item=BatchItem<Self::Construct>>,
+	) -> Result<(), Self::Error> {
+		self.0.insert_batch(items).map_err(|_| ())
+	}
 }
 
 /// Leakable value, whose default behavior of drop is to leak.
@@ -1,8 +1,13 @@
 //! Utilities
 
-use crate::{Construct, WriteBackend, Error};
+use crate::{Construct, ReadBackend, WriteBackend, Error};
 use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use alloc::string::String;
+use core::fmt::Write;
 use generic_array::ArrayLength;
+use core::cmp;
+use core::ops::Range;
 
 /// Required depth of given length.
 pub fn required_depth(len: u64) -> usize {
@@ -16,22 +21,24 @@ pub fn required_depth(len: u64) -> usize {
 }
 
 /// Serialize a vector at given depth.
-pub fn vector_tree<DB: WriteBackend>(values: &[<DB::Construct as Construct>::Value], db: &mut DB, max_len: Option<u64>) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> {
+pub fn vector_tree<DB: WriteBackend + ?Sized>(values: &[<DB::Construct as Construct>::Value], db: &mut DB, max_len: Option<u64>) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> {
 	let total_depth = required_depth(max_len.unwrap_or(values.len() as u64));
 
 	let mut current = values.iter().cloned().collect::<VecDeque<_>>();
 	let mut next = VecDeque::new();
 	for depth in (1..(total_depth + 1)).rev() {
 		let depth_to_bottom = total_depth - depth;
+		let mut batch = Vec::new();
 		while !current.is_empty() {
 			let left = current.pop_front().unwrap_or(<DB::Construct as Construct>::empty_at(db, depth_to_bottom)?);
 			let right = current.pop_front().unwrap_or(<DB::Construct as Construct>::empty_at(db, depth_to_bottom)?);
 
 			let key = <DB::Construct as Construct>::intermediate_of(&left, &right);
 
-			db.insert(key.clone(), (left, right))?;
+			batch.push((key.clone(), (left, right)));
 			next.push_back(key);
 		}
+		db.insert_batch(&mut batch.into_iter())?;
 		current = next;
 		next = VecDeque::new();
 	}
@@ -43,6 +50,114 @@ pub fn vector_tree<DB: WriteBackend>(values: &[<DB::Construct as Construct>::Val
 	}
 }
 
+/// Serialize a vector of basic leaf values at given depth, preprocessing
+/// each value with `Construct::hash_leaf` first. Leaves read back from the
+/// resulting tree are the preprocessed values.
+pub fn leaf_vector_tree<DB: WriteBackend + ?Sized>(values: &[<DB::Construct as Construct>::Value], db: &mut DB, max_len: Option<u64>) -> Result<<DB::Construct as Construct>::Value, Error<DB::Error>> {
+	let leaves = values.iter()
+		.map(<DB::Construct as Construct>::hash_leaf)
+		.collect::<Vec<_>>();
+	vector_tree(&leaves, db, max_len)
+}
+
+/// Streaming builder of a vector tree. Values are pushed one by one, and
+/// only one pending intermediate value per level is held, instead of all
+/// values. The result equals `vector_tree` of the same values.
+pub struct VectorTreeBuilder<C: Construct> {
+	max_len: Option<u64>,
+	len: u64,
+	pending: Vec<Option<C::Value>>,
+}
+
+impl<C: Construct> VectorTreeBuilder<C> {
+	/// Create a new builder.
+	pub fn new(max_len: Option<u64>) -> Self {
+		Self {
+			max_len,
+			len: 0,
+			pending: Vec::new(),
+		}
+	}
+
+	/// Number of values pushed.
+	pub fn len(&self) -> u64 {
+		self.len
+	}
+
+	/// Whether no value has been pushed.
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Push a new value, failing with `InvalidParameter` if `max_len` is
+	/// exceeded.
+	pub fn push<DB: WriteBackend<Construct=C> + ?Sized>(
+		&mut self,
+		db: &mut DB,
+		value: C::Value,
+	) -> Result<(), Error<DB::Error>> {
+		if let Some(max_len) = self.max_len {
+			if self.len >= max_len {
+				return Err(Error::InvalidParameter)
+			}
+		}
+		self.len += 1;
+
+		let mut current = value;
+		let mut level = 0;
+		loop {
+			if self.pending.len() <= level {
+				self.pending.push(None);
+			}
+
+			match self.pending[level].take() {
+				Some(left) => {
+					let key = C::intermediate_of(&left, &current);
+					db.insert(key.clone(), (left, current))?;
+					current = key;
+					level += 1;
+				},
+				None => {
+					self.pending[level] = Some(current);
+					return Ok(())
+				},
+			}
+		}
+	}
+
+	/// Finish building, returning the root.
+	pub fn finish<DB: WriteBackend<Construct=C> + ?Sized>(
+		mut self,
+		db: &mut DB,
+	) -> Result<C::Value, Error<DB::Error>> {
+		let total_depth = required_depth(self.max_len.unwrap_or(self.len));
+
+		let mut current: Option<C::Value> = None;
+		for level in 0..total_depth {
+			let pending = self.pending.get_mut(level).and_then(|value| value.take());
+			let (left, right) = match (pending, current) {
+				(Some(left), Some(right)) => (left, right),
+				(Some(left), None) => (left, C::empty_at(db, level)?),
+				(None, Some(left)) => (left, C::empty_at(db, level)?),
+				(None, None) => {
+					current = None;
+					continue
+				},
+			};
+
+			let key = C::intermediate_of(&left, &right);
+			db.insert(key.clone(), (left, right))?;
+			current = Some(key);
+		}
+
+		let top = self.pending.get_mut(total_depth).and_then(|value| value.take());
+		match current.or(top) {
+			Some(root) => Ok(root),
+			None => Ok(C::empty_at(db, total_depth)?),
+		}
+	}
+}
+
 /// Get the host len of a packed vector.
 pub fn host_max_len<Host: ArrayLength<u8>, Value: ArrayLength<u8>>(value_len: u64) -> u64 {
 	let host_array_len = Host::to_u64();
@@ -60,3 +175,119 @@ pub fn host_max_len<Host: ArrayLength<u8>, Value: ArrayLength<u8>>(value_len: u6
 pub fn host_len<Host: ArrayLength<u8>, Value: ArrayLength<u8>>(value_len: usize) -> usize {
 	host_max_len::<Host, Value>(value_len as u64) as usize
 }
+
+/// Get the host cell index and byte ranges covering the packed value at
+/// the given index. The first range is within the returned host cell, and
+/// any following ranges are in the consecutive host cells.
+///
+/// ```
+/// use bm::utils::coverings;
+/// use typenum::{U8, U32};
+///
+/// assert_eq!(coverings::<U32, U8>(3), (0, vec![24..32]));
+/// assert_eq!(coverings::<U32, U8>(4), (1, vec![0..8]));
+/// assert_eq!(coverings::<U8, U32>(1), (4, vec![0..8, 0..8, 0..8, 0..8]));
+/// ```
+pub fn coverings<Host: ArrayLength<u8>, Value: ArrayLength<u8>>(value_index: usize) -> (usize, Vec<Range<usize>>) {
+	let host_len = Host::to_usize();
+	let value_len = Value::to_usize();
+
+	let bytes = value_len * value_index;
+	let host_index = bytes / host_len;
+	let offset = bytes - host_len * host_index;
+
+	let mut ranges = Vec::new();
+	ranges.push(offset..cmp::min(offset + value_len, host_len));
+	let mut covered = cmp::min(offset + value_len, host_len) - offset;
+
+	while covered < value_len {
+		let rest = value_len - covered;
+		ranges.push(0..cmp::min(rest, host_len));
+		covered += cmp::min(rest, host_len);
+	}
+
+	(host_index, ranges)
+}
+
+/// Pretty-print the tree under `root` for debugging. Each line is a node
+/// with its hex value, indented by depth, and children are listed as
+/// `left` and `right`. Walking stops at nodes that are not intermediates,
+/// or below `max_depth`.
+pub fn debug_tree<DB: ReadBackend + ?Sized>(
+	root: &<DB::Construct as Construct>::Value,
+	db: &mut DB,
+	max_depth: usize,
+) -> Result<String, Error<DB::Error>> where
+	<DB::Construct as Construct>::Value: AsRef<[u8]>,
+{
+	let mut ret = String::new();
+	let mut pending = Vec::new();
+	pending.push(("root", root.clone(), 0));
+
+	while let Some((name, value, depth)) = pending.pop() {
+		for _ in 0..depth {
+			ret.push_str("  ");
+		}
+		ret.push_str(name);
+		ret.push_str(": 0x");
+		for byte in value.as_ref() {
+			write!(ret, "{:02x}", byte).expect("writing to string never fails; qed");
+		}
+		ret.push('\n');
+
+		if depth < max_depth {
+			if let Some((left, right)) = db.get(&value)? {
+				pending.push(("right", right, depth + 1));
+				pending.push(("left", left, depth + 1));
+			}
+		}
+	}
+
+	Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::InMemoryBackend;
+	use sha2::Sha256;
+
+	#[test]
+	fn test_debug_tree() {
+		let mut db = InMemoryBackend::<crate::InheritedDigestConstruct<Sha256>>::default();
+		let root = vector_tree(&[Default::default(); 3], &mut db, None).unwrap();
+		let printed = debug_tree(&root, &mut db, 8).unwrap();
+		let lines = printed.lines().collect::<Vec<_>>();
+
+		let empty = format!("0x{}", "00".repeat(32));
+		assert_eq!(lines.len(), 7);
+		assert!(lines[0].starts_with("root: 0x"));
+		assert!(lines[1].starts_with("  left: 0x"));
+		assert_eq!(lines[2], format!("    left: {}", empty));
+		assert_eq!(lines[3], format!("    right: {}", empty));
+		assert!(lines[4].starts_with("  right: 0x"));
+		assert_eq!(lines[6], format!("    right: {}", empty));
+
+		let truncated = debug_tree(&root, &mut db, 1).unwrap();
+		assert_eq!(truncated.lines().count(), 3);
+	}
+
+	#[test]
+	fn test_vector_tree_dyn() {
+		type Construct = crate::InheritedDigestConstruct<Sha256>;
+
+		let values = (0..5u8).map(|i| {
+			let mut value = <Construct as crate::Construct>::Value::default();
+			value[0] = i;
+			value
+		}).collect::<Vec<_>>();
+
+		let mut expected_db = InMemoryBackend::<Construct>::default();
+		let expected = vector_tree(&values, &mut expected_db, None).unwrap();
+
+		let mut db = InMemoryBackend::<Construct>::default();
+		let dyn_db: &mut dyn WriteBackend<Construct=Construct, Error=_> = &mut db;
+		assert_eq!(vector_tree(&values, dyn_db, None).unwrap(), expected);
+		assert_eq!(db.as_ref(), expected_db.as_ref());
+	}
+}
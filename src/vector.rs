@@ -1,10 +1,50 @@
 use crate::traits::{ReadBackend, WriteBackend, Construct, RootStatus, Owned, Dangling, Leak, Error, Tree, Sequence};
 use crate::raw::Raw;
 use crate::index::Index;
+use crate::utils::vector_tree;
+use crate::proving::{ProvingBackend, Proofs, CompactValue};
+use core::hash::Hash;
+use core::cmp;
+use alloc::vec::Vec;
 
 const ROOT_INDEX: Index = Index::root();
 const EXTEND_INDEX: Index = Index::root().left();
-const EMPTY_INDEX: Index = Index::root().right();
+
+fn uniform_at<C: Construct, DB: WriteBackend<Construct=C> + ?Sized>(
+	db: &mut DB,
+	uniforms: &mut Vec<C::Value>,
+	depth_to_bottom: usize,
+) -> Result<C::Value, Error<DB::Error>> {
+	while uniforms.len() <= depth_to_bottom {
+		let last = uniforms.last().expect("uniforms always start with the filled value; qed").clone();
+		let key = C::intermediate_of(&last, &last);
+		db.insert(key.clone(), (last.clone(), last))?;
+		uniforms.push(key);
+	}
+
+	Ok(uniforms[depth_to_bottom].clone())
+}
+
+fn filled_at<C: Construct, DB: WriteBackend<Construct=C> + ?Sized>(
+	db: &mut DB,
+	uniforms: &mut Vec<C::Value>,
+	depth_to_bottom: usize,
+	count: usize,
+) -> Result<C::Value, Error<DB::Error>> {
+	if count == 0 {
+		return Ok(C::empty_at(db, depth_to_bottom)?)
+	}
+	if count == 1 << depth_to_bottom {
+		return uniform_at(db, uniforms, depth_to_bottom)
+	}
+
+	let half = 1 << (depth_to_bottom - 1);
+	let left = filled_at(db, uniforms, depth_to_bottom - 1, cmp::min(count, half))?;
+	let right = filled_at(db, uniforms, depth_to_bottom - 1, count.saturating_sub(half))?;
+	let key = C::intermediate_of(&left, &right);
+	db.insert(key.clone(), (left, right))?;
+	Ok(key)
+}
 
 /// `Vector` with owned root.
 pub type OwnedVector<C> = Vector<Owned, C>;
@@ -26,15 +66,23 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 
 	fn extend<DB: WriteBackend<Construct=C> + ?Sized>(
 		&mut self,
-		db: &mut DB
+		db: &mut DB,
+		value: C::Value,
 	) -> Result<(), Error<DB::Error>> {
-		let root = self.root();
-		let mut new_raw = Raw::default();
-		let empty = C::empty_at(db, self.depth())?;
-		new_raw.set(db, EXTEND_INDEX, root)?;
-		new_raw.set(db, EMPTY_INDEX, empty)?;
-		self.raw.set(db, ROOT_INDEX, Default::default())?;
-		self.raw = new_raw;
+		// Build the new right half with `value` at its leftmost leaf
+		// directly, so that no transient root is left in the backend.
+		let mut right = value;
+		for depth_to_bottom in 0..self.depth() {
+			let empty = C::empty_at(db, depth_to_bottom)?;
+			let key = C::intermediate_of(&right, &empty);
+			db.insert(key.clone(), (right, empty))?;
+			right = key;
+		}
+
+		let left = self.root();
+		let root = C::intermediate_of(&left, &right);
+		db.insert(root.clone(), (left, right))?;
+		self.raw.set(db, ROOT_INDEX, root)?;
 		Ok(())
 	}
 
@@ -44,7 +92,7 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 	) -> Result<(), Error<DB::Error>> {
 		match self.raw.get(db, EXTEND_INDEX)? {
 			Some(extended_value) => { self.raw.set(db, ROOT_INDEX, extended_value)?; },
-			None => { self.raw.set(db, ROOT_INDEX, Default::default())?; },
+			None => { self.raw.set(db, ROOT_INDEX, C::empty_end())?; },
 		}
 		Ok(())
 	}
@@ -76,6 +124,16 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 		depth
 	}
 
+	/// Generalized index of the leaf at index, relative to the vector root.
+	/// Returns `None` if the index is out of range.
+	pub fn leaf_index(&self, index: usize) -> Option<Index> {
+		if index >= self.len() {
+			return None
+		}
+
+		Some(self.raw_index(index))
+	}
+
 	/// Get value at index.
 	pub fn get<DB: ReadBackend<Construct=C> + ?Sized>(
 		&self,
@@ -90,6 +148,30 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 		self.raw.get(db, raw_index)?.ok_or(Error::CorruptedDatabase)
 	}
 
+	/// Read all values of the vector, in index order. When the value type
+	/// implements serde's `Serialize`, the result can be serialized to dump
+	/// the logical contents rather than the tree nodes.
+	pub fn to_values<DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+	) -> Result<Vec<C::Value>, Error<DB::Error>> {
+		(0..self.len()).map(|i| self.get(db, i)).collect()
+	}
+
+	/// Create a compact proof of the value at index against the vector root.
+	pub fn prove_element<DB: ReadBackend<Construct=C> + ?Sized>(
+		&self,
+		db: &mut DB,
+		index: usize
+	) -> Result<CompactValue<C::Value>, Error<DB::Error>> where
+		C::Value: Eq + Hash + Ord,
+	{
+		let mut proving = ProvingBackend::new(db);
+		self.get(&mut proving, index)?;
+
+		Ok(Proofs::from(proving).into_compact(self.root()))
+	}
+
 	/// Set value at index.
 	pub fn set<DB: WriteBackend<Construct=C> + ?Sized>(
 		&mut self,
@@ -106,6 +188,20 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 		Ok(())
 	}
 
+	/// Set all values of the vector to the given value. Subtrees that
+	/// are fully filled are only hashed once per depth.
+	pub fn fill<DB: WriteBackend<Construct=C> + ?Sized>(
+		&mut self,
+		db: &mut DB,
+		value: C::Value
+	) -> Result<(), Error<DB::Error>> {
+		let mut uniforms = Vec::new();
+		uniforms.push(value);
+		let root = filled_at(db, &mut uniforms, self.depth(), self.len())?;
+		self.raw.set(db, ROOT_INDEX, root)?;
+		Ok(())
+	}
+
 	/// Push a new value to the vector.
 	pub fn push<DB: WriteBackend<Construct=C> + ?Sized>(
 		&mut self,
@@ -117,7 +213,9 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 			if self.max_len.is_some() {
 				return Err(Error::AccessOverflowed)
 			} else {
-				self.extend(db)?;
+				self.extend(db, value)?;
+				self.len = old_len + 1;
+				return Ok(())
 			}
 		}
 		let len = old_len + 1;
@@ -129,6 +227,45 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 		Ok(())
 	}
 
+	/// Append all values to the vector. The depth is grown once per new
+	/// level and the new leaves are set in one batch. Fails with
+	/// `AccessOverflowed`, leaving the vector unchanged, if it would exceed
+	/// the maximum length.
+	pub fn extend_from_slice<DB: WriteBackend<Construct=C> + ?Sized>(
+		&mut self,
+		db: &mut DB,
+		values: &[C::Value],
+	) -> Result<(), Error<DB::Error>> {
+		if values.is_empty() {
+			return Ok(())
+		}
+
+		let old_len = self.len();
+		let len = old_len + values.len();
+		if let Some(max_len) = self.max_len {
+			if len as u64 > max_len {
+				return Err(Error::AccessOverflowed)
+			}
+		}
+
+		let mut depth = self.depth();
+		while (len as u64) > (1u64 << depth) && self.max_len.is_none() {
+			let left = self.root();
+			let right = C::empty_at(db, depth)?;
+			let root = C::intermediate_of(&left, &right);
+			db.insert(root.clone(), (left, right))?;
+			self.raw.set(db, ROOT_INDEX, root)?;
+			depth += 1;
+		}
+		self.len = len;
+
+		let items = values.iter()
+			.enumerate()
+			.map(|(i, value)| (self.raw_index(old_len + i), value.clone()))
+			.collect::<Vec<_>>();
+		self.raw.set_batch(db, &items)
+	}
+
 	/// Pop a value from the vector.
 	pub fn pop<DB: WriteBackend<Construct=C> + ?Sized>(
 		&mut self,
@@ -144,30 +281,118 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 		let raw_index = self.raw_index(index);
 		let value = self.raw.get(db, raw_index)?.ok_or(Error::CorruptedDatabase)?;
 
-		let mut empty_depth_to_bottom = 0;
-		let mut replace_index = raw_index;
-		loop {
-			if let Some(parent) = replace_index.parent() {
-				if parent.left() == replace_index {
-					replace_index = parent;
-					empty_depth_to_bottom += 1;
+		if self.max_len.is_none() && self.depth() > 0 &&
+			(len as u64) <= self.current_max_len() / 2
+		{
+			// The popped value is the only one in the right half, so
+			// the tree shrinks directly into its left half.
+			self.shrink(db)?;
+		} else {
+			let mut empty_depth_to_bottom = 0;
+			let mut replace_index = raw_index;
+			loop {
+				if let Some(parent) = replace_index.parent() {
+					if parent.left() == replace_index {
+						replace_index = parent;
+						empty_depth_to_bottom += 1;
+					} else {
+						break
+					}
 				} else {
 					break
 				}
-			} else {
-				break
 			}
+			let empty = C::empty_at(db, empty_depth_to_bottom)?;
+			self.raw.set(db, replace_index, empty)?;
 		}
-		let empty = C::empty_at(db, empty_depth_to_bottom)?;
-		self.raw.set(db, replace_index, empty)?;
 
-		if (len as u64) <= self.current_max_len() / 2 {
-			if self.max_len.is_none() {
-				self.shrink(db)?;
+		self.len = len;
+		Ok(Some(value))
+	}
+
+	/// Iterate over the vector in successive chunks of `chunk_size`
+	/// values. The last chunk may be shorter. Panics if `chunk_size` is
+	/// zero.
+	pub fn chunks<'a, DB: ReadBackend<Construct=C> + ?Sized>(
+		&'a self,
+		db: &'a mut DB,
+		chunk_size: usize,
+	) -> impl Iterator<Item=Result<Vec<C::Value>, Error<DB::Error>>> + 'a {
+		assert!(chunk_size != 0, "chunk size must be non-zero");
+
+		(0..self.len()).step_by(chunk_size).map(move |start| {
+			let end = cmp::min(start + chunk_size, self.len());
+			(start..end).map(|i| self.get(db, i)).collect()
+		})
+	}
+
+	/// Pop up to `count` values from the vector, returned in index order.
+	/// Trailing subtrees are replaced by empty values in bulk, and the
+	/// tree is shrunk at most once per depth.
+	pub fn pop_many<DB: WriteBackend<Construct=C> + ?Sized>(
+		&mut self,
+		db: &mut DB,
+		count: usize,
+	) -> Result<Vec<C::Value>, Error<DB::Error>> {
+		let old_len = self.len();
+		let len = old_len - cmp::min(count, old_len);
+
+		let mut values = Vec::with_capacity(old_len - len);
+		for i in len..old_len {
+			values.push(self.get(db, i)?);
+		}
+
+		let old_depth = self.depth();
+		let old_max_len = self.current_max_len() as usize;
+		let mut index = len;
+		while index < old_len {
+			let mut empty_depth_to_bottom = 0;
+			while empty_depth_to_bottom < old_depth &&
+				index & ((1 << (empty_depth_to_bottom + 1)) - 1) == 0 &&
+				index + (1 << (empty_depth_to_bottom + 1)) <= old_max_len
+			{
+				empty_depth_to_bottom += 1;
 			}
+
+			let replace_index = Index::from_depth(
+				index >> empty_depth_to_bottom,
+				old_depth - empty_depth_to_bottom
+			);
+			let empty = C::empty_at(db, empty_depth_to_bottom)?;
+			self.raw.set(db, replace_index, empty)?;
+			index += 1 << empty_depth_to_bottom;
 		}
+
 		self.len = len;
-		Ok(Some(value))
+		for _ in self.depth()..old_depth {
+			self.shrink(db)?;
+		}
+
+		Ok(values)
+	}
+
+	/// Create a new owned vector of the same length and maximum length,
+	/// with each value transformed by the given function. The new tree is
+	/// built in a single pass.
+	pub fn map_into<DB: WriteBackend<Construct=C> + ?Sized, F: FnMut(C::Value) -> C::Value>(
+		&self,
+		db: &mut DB,
+		mut f: F,
+	) -> Result<Vector<Owned, C>, Error<DB::Error>> {
+		let mut values = Vec::with_capacity(self.len());
+		for i in 0..self.len() {
+			values.push(f(self.get(db, i)?));
+		}
+		let root = vector_tree(&values, db, self.max_len)?;
+
+		let mut raw = Raw::<Owned, C>::default();
+		raw.set(db, ROOT_INDEX, root)?;
+
+		Ok(Vector {
+			raw,
+			len: self.len,
+			max_len: self.max_len,
+		})
 	}
 
 	/// Get the length of the tuple.
@@ -179,6 +404,45 @@ impl<R: RootStatus, C: Construct> Vector<R, C> {
 	pub fn from_raw(raw: Raw<R, C>, len: usize, max_len: Option<u64>) -> Self {
 		Self { raw, len, max_len }
 	}
+
+	/// Create the vector from leaked metadata, checking it against the
+	/// tree. The length must be within the maximum length, the leaf of
+	/// the last value must be reachable, and the first leaf past the
+	/// length, if any, must be empty. Fails with `CorruptedDatabase`
+	/// otherwise. Only lengths that change the tree shape or leave a
+	/// non-empty value past the end are detected, so a vector whose
+	/// trailing values are empty cannot be told apart from a shorter one.
+	/// Leaves under a collapsed empty subtree are read as empty, as
+	/// `Raw::get_collapsed` does.
+	pub fn checked_from_leaked<DB: ReadBackend<Construct=C> + ?Sized>(
+		metadata: <Self as Leak>::Metadata,
+		db: &mut DB,
+	) -> Result<Self, Error<DB::Error>> where
+		C::Value: PartialEq,
+	{
+		let ret = Self::from_leaked(metadata);
+
+		if let Some(max_len) = ret.max_len {
+			if ret.len as u64 > max_len {
+				return Err(Error::CorruptedDatabase)
+			}
+		}
+
+		if ret.len > 0 {
+			ret.raw.get_collapsed(db, ret.raw_index(ret.len - 1))?
+				.ok_or(Error::CorruptedDatabase)?;
+		}
+
+		if (ret.len as u64) < ret.current_max_len() {
+			let next = ret.raw.get_collapsed(db, ret.raw_index(ret.len))?
+				.ok_or(Error::CorruptedDatabase)?;
+			if next != C::empty_end() {
+				return Err(Error::CorruptedDatabase)
+			}
+		}
+
+		Ok(ret)
+	}
 }
 
 impl<R: RootStatus, C: Construct> Tree for Vector<R, C> {
@@ -226,6 +490,20 @@ impl<R: RootStatus, C: Construct> Leak for Vector<R, C> {
 	}
 }
 
+/// Vectors are equal when their roots and lengths are equal. This is a
+/// hash equality, and does not compare backend contents.
+impl<R1: RootStatus, R2: RootStatus, C: Construct> PartialEq<Vector<R2, C>> for Vector<R1, C> where
+	C::Value: PartialEq,
+{
+	fn eq(&self, other: &Vector<R2, C>) -> bool {
+		self.root() == other.root() && self.len() == other.len()
+	}
+}
+
+impl<R: RootStatus, C: Construct> Eq for Vector<R, C> where
+	C::Value: Eq,
+{ }
+
 impl<C: Construct> Vector<Owned, C> {
 	/// Create a new tuple.
 	pub fn create<DB: WriteBackend<Construct=C> + ?Sized>(
@@ -258,6 +536,43 @@ impl<C: Construct> Vector<Owned, C> {
 			max_len,
 		})
 	}
+
+	/// Create a new tuple, with each value initialized by the given
+	/// function of its index. The tree is built in a single pass.
+	pub fn create_with<DB: WriteBackend<Construct=C> + ?Sized, F: FnMut(usize) -> C::Value>(
+		db: &mut DB,
+		len: usize,
+		max_len: Option<u64>,
+		f: F,
+	) -> Result<Self, Error<DB::Error>> {
+		if let Some(max_len) = max_len {
+			if (len as u64) < max_len || max_len == 0 {
+				return Err(Error::InvalidParameter)
+			}
+		}
+
+		let values = (0..len).map(f).collect::<Vec<_>>();
+		let root = vector_tree(&values, db, max_len)?;
+
+		let mut raw = Raw::<Owned, C>::default();
+		raw.set(db, ROOT_INDEX, root)?;
+
+		Ok(Self {
+			raw,
+			len,
+			max_len,
+		})
+	}
+
+	/// Create a new tuple from the given values. This is the inverse of
+	/// `to_values`.
+	pub fn from_slice<DB: WriteBackend<Construct=C> + ?Sized>(
+		db: &mut DB,
+		values: &[C::Value],
+		max_len: Option<u64>,
+	) -> Result<Self, Error<DB::Error>> {
+		Self::create_with(db, values.len(), max_len, |i| values[i].clone())
+	}
 }
 
 impl<R: RootStatus, C: Construct> Raw<R, C> {
@@ -266,3 +581,338 @@ impl<R: RootStatus, C: Construct> Raw<R, C> {
 		Vector::from_raw(self, len, max_len)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generic_array::{arr, arr_impl};
+	use sha2::Sha256;
+
+	type Construct = crate::InheritedDigestConstruct<Sha256>;
+	type InMemory = crate::memory::InMemoryBackend<Construct>;
+
+	macro_rules! sinarr {
+		( $x:expr ) => (
+			arr![u8;
+				 $x, 0, 0, 0, 0, 0, 0, 0,
+				 0, 0, 0, 0, 0, 0, 0, 0,
+				 0, 0, 0, 0, 0, 0, 0, 0,
+				 0, 0, 0, 0, 0, 0, 0, 0]
+		)
+	}
+
+	#[test]
+	fn test_set_order_independent() {
+		let mut db1 = InMemory::default();
+		let mut db2 = InMemory::default();
+		let mut vec1 = OwnedVector::<Construct>::create(&mut db1, 0, None).unwrap();
+		let mut vec2 = OwnedVector::<Construct>::create(&mut db2, 100, None).unwrap();
+
+		for i in 0..100 {
+			vec1.push(&mut db1, sinarr!(i as u8 + 1)).unwrap();
+		}
+		for i in 0..100 {
+			let index = (i * 37 + 11) % 100;
+			vec2.set(&mut db2, index, sinarr!(index as u8 + 1)).unwrap();
+		}
+		assert_eq!(vec1.root(), vec2.root());
+		assert_eq!(db1.as_ref(), db2.as_ref());
+	}
+
+	struct PrefixedConstruct;
+
+	impl crate::Construct for PrefixedConstruct {
+		type Value = <Construct as crate::Construct>::Value;
+
+		fn intermediate_of(left: &Self::Value, right: &Self::Value) -> Self::Value {
+			Construct::intermediate_of(left, right)
+		}
+
+		fn hash_leaf(value: &Self::Value) -> Self::Value {
+			Construct::intermediate_of(&Default::default(), value)
+		}
+
+		fn empty_at<DB: WriteBackend<Construct=Self> + ?Sized>(
+			_db: &mut DB,
+			_depth_to_bottom: usize
+		) -> Result<Self::Value, DB::Error> {
+			Ok(Default::default())
+		}
+	}
+
+	#[test]
+	fn test_hash_leaf() {
+		let mut db = crate::memory::InMemoryBackend::<PrefixedConstruct>::default();
+		let values = (0..4).map(|i| sinarr!(i as u8)).collect::<Vec<_>>();
+		let root = crate::utils::leaf_vector_tree(&values, &mut db, None).unwrap();
+
+		let leaf = |i: u8| <PrefixedConstruct as crate::Construct>::hash_leaf(&sinarr!(i));
+		let h = <PrefixedConstruct as crate::Construct>::intermediate_of;
+		assert_eq!(root, h(&h(&leaf(0), &leaf(1)), &h(&leaf(2), &leaf(3))));
+
+		let mut db1 = crate::memory::InMemoryBackend::<PrefixedConstruct>::default();
+		let mut db2 = crate::memory::InMemoryBackend::<PrefixedConstruct>::default();
+		let mut db3 = crate::memory::InMemoryBackend::<PrefixedConstruct>::default();
+		let vec1 = OwnedVector::<PrefixedConstruct>::create_with(&mut db1, 5, None, |i| sinarr!(i as u8)).unwrap();
+		let mut vec2 = OwnedVector::<PrefixedConstruct>::create(&mut db2, 0, None).unwrap();
+		for i in 0..5 {
+			vec2.push(&mut db2, sinarr!(i as u8)).unwrap();
+		}
+		let mut vec3 = OwnedVector::<PrefixedConstruct>::create(&mut db3, 5, None).unwrap();
+		for i in 0..5 {
+			vec3.set(&mut db3, i, sinarr!(i as u8)).unwrap();
+		}
+		assert_eq!(vec1.root(), vec2.root());
+		assert_eq!(vec1.root(), vec3.root());
+		assert_eq!(vec1.get(&mut db1, 2).unwrap(), sinarr!(2));
+
+		let values = vec1.to_values(&mut db1).unwrap();
+		let vec4 = OwnedVector::<PrefixedConstruct>::from_slice(&mut db2, &values, None).unwrap();
+		let vec5 = vec1.map_into(&mut db1, |value| value).unwrap();
+		assert_eq!(vec4.root(), vec1.root());
+		assert_eq!(vec5.root(), vec1.root());
+	}
+
+	#[test]
+	fn test_fill() {
+		for &(len, max_len) in &[(0, None), (1, None), (13, None), (16, None), (13, Some(13))] {
+			let mut db1 = InMemory::default();
+			let mut db2 = InMemory::default();
+			let mut vec1 = OwnedVector::<Construct>::create(&mut db1, len, max_len).unwrap();
+			let mut vec2 = OwnedVector::<Construct>::create(&mut db2, len, max_len).unwrap();
+
+			vec1.fill(&mut db1, sinarr!(7)).unwrap();
+			for i in 0..len {
+				vec2.set(&mut db2, i, sinarr!(7)).unwrap();
+			}
+			assert_eq!(vec1.root(), vec2.root());
+			assert_eq!(db1.as_ref(), db2.as_ref());
+			for i in 0..len {
+				assert_eq!(vec1.get(&mut db1, i).unwrap(), sinarr!(7));
+			}
+		}
+	}
+
+	#[test]
+	fn test_prove_element() {
+		let mut db = InMemory::default();
+		let vec = OwnedVector::<Construct>::create_with(&mut db, 13, None, |i| sinarr!(i as u8)).unwrap();
+
+		let compact = vec.prove_element(&mut db, 5).unwrap();
+		let mut verifier = InMemory::default();
+		assert_eq!(verifier.populate_compact(compact), vec.root());
+		let proven = DanglingVector::<Construct>::from_leaked((vec.root(), 13, None));
+		assert_eq!(proven.get(&mut verifier, 5).unwrap(), sinarr!(5));
+		assert_eq!(proven.get(&mut verifier, 6), Err(Error::CorruptedDatabase));
+	}
+
+	#[test]
+	fn test_leaf_index() {
+		let mut db = InMemory::default();
+		let vec = OwnedVector::<Construct>::create_with(&mut db, 13, None, |i| sinarr!(i as u8)).unwrap();
+
+		assert_eq!(vec.leaf_index(0), Index::from_one(16));
+		assert_eq!(vec.leaf_index(5), Index::from_one(21));
+		assert_eq!(vec.leaf_index(12), Index::from_one(28));
+		assert_eq!(vec.leaf_index(13), None);
+
+		let leaf = vec.leaf_index(5).unwrap();
+		assert_eq!(vec.raw.get(&mut db, leaf).unwrap(), Some(sinarr!(5)));
+	}
+
+	#[test]
+	fn test_eq() {
+		let mut db = InMemory::default();
+		let vec1 = OwnedVector::<Construct>::create_with(&mut db, 13, None, |i| sinarr!(i as u8)).unwrap();
+		let mut vec2 = OwnedVector::<Construct>::create_with(&mut db, 13, None, |i| sinarr!(i as u8)).unwrap();
+		assert!(vec1 == vec2);
+		assert!(vec1 == DanglingVector::<Construct>::from_leaked(vec1.metadata()));
+
+		vec2.set(&mut db, 3, sinarr!(0)).unwrap();
+		assert!(vec1 != vec2);
+		assert!(vec1 != DanglingVector::<Construct>::from_leaked((vec1.root(), 12, None)));
+	}
+
+	#[test]
+	fn test_to_values_from_slice() {
+		for &(len, max_len) in &[(0, None), (1, None), (13, None), (16, Some(16))] {
+			let mut db = InMemory::default();
+			let vec = OwnedVector::<Construct>::create_with(&mut db, len, max_len, |i| sinarr!(i as u8)).unwrap();
+			let values = vec.to_values(&mut db).unwrap();
+			assert_eq!(values.len(), len);
+
+			let other = OwnedVector::<Construct>::from_slice(&mut db, &values, max_len).unwrap();
+			assert_eq!(other.root(), vec.root());
+			assert_eq!(other.to_values(&mut db).unwrap(), values);
+		}
+	}
+
+	#[test]
+	fn test_create_with() {
+		for &(len, max_len) in &[(0, None), (1, None), (13, None), (16, None), (13, Some(13))] {
+			let mut db1 = InMemory::default();
+			let mut db2 = InMemory::default();
+			let vec1 = OwnedVector::<Construct>::create_with(&mut db1, len, max_len, |i| sinarr!(i as u8)).unwrap();
+			let mut vec2 = OwnedVector::<Construct>::create(&mut db2, len, max_len).unwrap();
+
+			for i in 0..len {
+				vec2.set(&mut db2, i, sinarr!(i as u8)).unwrap();
+			}
+			assert_eq!(vec1.root(), vec2.root());
+			assert_eq!(vec1.len(), len);
+			for i in 0..len {
+				assert_eq!(vec1.get(&mut db1, i).unwrap(), sinarr!(i as u8));
+			}
+		}
+	}
+
+	#[test]
+	fn test_map_into() {
+		for &(len, max_len) in &[(0, None), (1, None), (13, None), (16, Some(16))] {
+			let mut db = InMemory::default();
+			let vec = OwnedVector::<Construct>::create_with(&mut db, len, max_len, |i| sinarr!(i as u8)).unwrap();
+			let mapped = vec.map_into(&mut db, |mut value| { value[1] = 0xff; value }).unwrap();
+
+			let mut expected = OwnedVector::<Construct>::create(&mut db, len, max_len).unwrap();
+			for i in 0..len {
+				let mut value = vec.get(&mut db, i).unwrap();
+				value[1] = 0xff;
+				expected.set(&mut db, i, value).unwrap();
+			}
+			assert!(mapped == expected);
+			assert_eq!(mapped.max_len(), max_len);
+			for i in 0..len {
+				assert_eq!(mapped.get(&mut db, i).unwrap(), expected.get(&mut db, i).unwrap());
+			}
+		}
+	}
+
+	#[test]
+	fn test_extend_from_slice() {
+		for &(initial, count) in &[(0, 0), (0, 1), (0, 5), (0, 17), (3, 14), (4, 4), (16, 1)] {
+			let values = (0..count).map(|i| sinarr!(i as u8 + 100)).collect::<Vec<_>>();
+
+			let mut pushed_db = InMemory::default();
+			let mut pushed = OwnedVector::<Construct>::create(&mut pushed_db, 0, None).unwrap();
+			let mut db = InMemory::default();
+			let mut vec = OwnedVector::<Construct>::create(&mut db, 0, None).unwrap();
+			for i in 0..initial {
+				pushed.push(&mut pushed_db, sinarr!(i as u8)).unwrap();
+				vec.push(&mut db, sinarr!(i as u8)).unwrap();
+			}
+
+			for value in &values {
+				pushed.push(&mut pushed_db, *value).unwrap();
+			}
+			vec.extend_from_slice(&mut db, &values).unwrap();
+
+			assert_eq!(vec.len(), pushed.len());
+			assert_eq!(vec.root(), pushed.root());
+			assert_eq!(db.as_ref(), pushed_db.as_ref());
+		}
+
+		let mut db = InMemory::default();
+		let mut vec = OwnedVector::<Construct>::create(&mut db, 8, Some(8)).unwrap();
+		for _ in 0..3 {
+			vec.pop(&mut db).unwrap();
+		}
+		let root = vec.root();
+		assert_eq!(vec.extend_from_slice(&mut db, &[sinarr!(1), sinarr!(2), sinarr!(3), sinarr!(4)]), Err(Error::AccessOverflowed));
+		assert_eq!(vec.root(), root);
+		vec.extend_from_slice(&mut db, &[sinarr!(1), sinarr!(2), sinarr!(3)]).unwrap();
+		assert_eq!(vec.len(), 8);
+		assert_eq!(vec.get(&mut db, 7).unwrap(), sinarr!(3));
+	}
+
+	#[test]
+	fn test_pop_many() {
+		for &(len, max_len, count) in &[(100, None, 30), (100, None, 100), (100, None, 120), (64, Some(64), 30), (1, None, 1)] {
+			let build = |db: &mut InMemory| {
+				let mut vec = OwnedVector::<Construct>::create(db, 0, None).unwrap();
+				if max_len.is_some() {
+					vec = OwnedVector::<Construct>::create(db, len, max_len).unwrap();
+					for i in 0..len {
+						vec.set(db, i, sinarr!(i as u8)).unwrap();
+					}
+				} else {
+					for i in 0..len {
+						vec.push(db, sinarr!(i as u8)).unwrap();
+					}
+				}
+				vec
+			};
+
+			let mut popped_db = InMemory::default();
+			let mut popped = build(&mut popped_db);
+			let mut expected_values = Vec::new();
+			for _ in 0..cmp::min(count, len) {
+				expected_values.insert(0, popped.pop(&mut popped_db).unwrap().unwrap());
+			}
+
+			let mut db = InMemory::default();
+			let mut vec = build(&mut db);
+
+			let values = vec.pop_many(&mut db, count).unwrap();
+			assert_eq!(values, expected_values);
+			assert!(vec == popped);
+			assert_eq!(vec.len(), len.saturating_sub(count));
+			for i in 0..vec.len() {
+				assert_eq!(vec.get(&mut db, i).unwrap(), sinarr!(i as u8));
+			}
+		}
+	}
+
+	#[test]
+	fn test_chunks() {
+		let mut db = InMemory::default();
+		let vec = OwnedVector::<Construct>::create_with(&mut db, 10, None, |i| sinarr!(i as u8)).unwrap();
+
+		let chunks = vec.chunks(&mut db, 3).collect::<Result<Vec<_>, _>>().unwrap();
+		assert_eq!(chunks.iter().map(|chunk| chunk.len()).collect::<Vec<_>>(), vec![3, 3, 3, 1]);
+		assert_eq!(chunks.concat(), (0..10).map(|i| sinarr!(i as u8)).collect::<Vec<_>>());
+
+		let empty = OwnedVector::<Construct>::create(&mut db, 0, None).unwrap();
+		assert_eq!(empty.chunks(&mut db, 3).count(), 0);
+	}
+
+	#[test]
+	fn test_checked_from_leaked() {
+		let mut db = InMemory::default();
+		let mut vec = OwnedVector::<Construct>::create(&mut db, 0, None).unwrap();
+		for i in 1..=5 {
+			vec.push(&mut db, sinarr!(i as u8)).unwrap();
+		}
+		let root = vec.root();
+
+		let loaded = DanglingVector::<Construct>::checked_from_leaked((root, 5, None), &mut db).unwrap();
+		assert_eq!(loaded.get(&mut db, 4).unwrap(), sinarr!(5));
+		assert!(DanglingVector::<Construct>::checked_from_leaked((root, 3, None), &mut db).is_err());
+		assert!(DanglingVector::<Construct>::checked_from_leaked((root, 9, None), &mut db).is_err());
+		assert!(DanglingVector::<Construct>::checked_from_leaked((root, 5, Some(4)), &mut db).is_err());
+		assert!(DanglingVector::<Construct>::checked_from_leaked((root, 5, None), &mut InMemory::default()).is_err());
+
+		let bounded = OwnedVector::<Construct>::create_with(&mut db, 8, Some(8), |i| sinarr!(i as u8 + 1)).unwrap();
+		assert!(DanglingVector::<Construct>::checked_from_leaked((bounded.root(), 8, Some(8)), &mut db).is_ok());
+		assert!(DanglingVector::<Construct>::checked_from_leaked((bounded.root(), 6, Some(8)), &mut db).is_err());
+	}
+
+	#[test]
+	fn test_checked_from_leaked_unit() {
+		type Unit = crate::UnitDigestConstruct<Sha256>;
+
+		let mut db = crate::memory::InMemoryBackend::<Unit>::default();
+		let mut vec = OwnedVector::<Unit>::create(&mut db, 0, None).unwrap();
+		for i in 1..=6 {
+			vec.push(&mut db, sinarr!(i as u8)).unwrap();
+		}
+		let root = vec.root();
+
+		let loaded = DanglingVector::<Unit>::checked_from_leaked((root, 6, None), &mut db).unwrap();
+		assert_eq!(loaded.get(&mut db, 5).unwrap(), sinarr!(6));
+		assert!(DanglingVector::<Unit>::checked_from_leaked((root, 5, None), &mut db).is_err());
+		assert!(DanglingVector::<Unit>::checked_from_leaked((root, 9, None), &mut db).is_err());
+
+		let empty = OwnedVector::<Unit>::create(&mut db, 3, None).unwrap();
+		assert!(DanglingVector::<Unit>::checked_from_leaked((empty.root(), 3, None), &mut db).is_ok());
+	}
+}
@@ -0,0 +1,75 @@
+use bm::{OwnedList, WriteBackend, InMemoryBackend, ProvingBackend, DynBackend, Tree};
+use sha2::Sha256;
+use generic_array::GenericArray;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Ord, PartialOrd, Hash)]
+struct ListValue([u8; 32]);
+
+impl AsRef<[u8]> for ListValue {
+	fn as_ref(&self) -> &[u8] {
+		self.0.as_ref()
+	}
+}
+
+impl From<usize> for ListValue {
+	fn from(value: usize) -> Self {
+		let mut bytes = [0u8; 32];
+		bytes[0..8].copy_from_slice(&(value as u64).to_le_bytes()[..]);
+		ListValue(bytes)
+	}
+}
+
+impl From<ListValue> for usize {
+	fn from(value: ListValue) -> usize {
+		let mut raw = [0u8; 8];
+		raw.copy_from_slice(&value.0[0..8]);
+		u64::from_le_bytes(raw) as usize
+	}
+}
+
+impl From<GenericArray<u8, typenum::U32>> for ListValue {
+	fn from(array: GenericArray<u8, typenum::U32>) -> ListValue {
+		let mut ret = [0u8; 32];
+		ret.copy_from_slice(&array[0..32]);
+		ListValue(ret)
+	}
+}
+
+type Construct = bm::InheritedDigestConstruct<Sha256, ListValue>;
+type InMemory = InMemoryBackend<Construct>;
+
+fn build_list(db: &mut dyn WriteBackend<Construct=Construct, Error=()>) -> ListValue {
+	let mut list = OwnedList::<Construct>::create(db, None).unwrap();
+	for i in 0..20 {
+		list.push(db, i.into()).unwrap();
+	}
+	assert_eq!(list.get(db, 13).unwrap(), 13.into());
+	assert_eq!(list.pop(db).unwrap(), Some(19.into()));
+	list.root()
+}
+
+#[test]
+fn dyn_backends() {
+	let mut expected_db = InMemory::default();
+	let mut expected = OwnedList::<Construct>::create(&mut expected_db, None).unwrap();
+	for i in 0..19 {
+		expected.push(&mut expected_db, i.into()).unwrap();
+	}
+
+	let mut proved_db = InMemory::default();
+	let backends: Vec<Box<dyn WriteBackend<Construct=Construct, Error=()> + '_>> = vec![
+		Box::new(DynBackend(InMemory::default())),
+		Box::new(DynBackend(ProvingBackend::new(&mut proved_db))),
+	];
+
+	for mut backend in backends {
+		assert_eq!(build_list(&mut *backend), expected.root());
+	}
+	assert!(proved_db.as_ref().contains_key(&expected.root()));
+
+	let mut db = InMemory::default();
+	let dyn_db: &mut dyn WriteBackend<Construct=Construct, Error=_> = &mut db;
+	let mut list = OwnedList::<Construct>::create(dyn_db, None).unwrap();
+	list.push(dyn_db, 1.into()).unwrap();
+	assert_eq!(list.get(&mut db, 0).unwrap(), 1.into());
+}
@@ -1,4 +1,5 @@
-use bm::{OwnedList, ProvingBackend, Sequence, Proofs};
+use bm::{OwnedList, ProvingBackend, Sequence, Proofs, QueryProver, OwnedRaw, Index, Tree, CompactValue};
+use std::collections::HashSet;
 use sha2::Sha256;
 use generic_array::GenericArray;
 
@@ -75,3 +76,169 @@ fn basic_proving_vec() {
 	assert_eq!(proved_vec.get(&mut proved, 5usize.into()).unwrap(), 5usize.into());
 	assert_eq!(proved_vec.get(&mut proved, 7usize.into()).unwrap(), 7usize.into());
 }
+
+#[test]
+fn stacked_proving_vec() {
+	let mut db = InMemory::default();
+	let mut vec = OwnedList::create(&mut db, None).unwrap();
+	for i in 0..100 {
+		vec.push(&mut db, i.into()).unwrap();
+	}
+
+	let mut inner = ProvingBackend::new(&mut db);
+	let mut outer = ProvingBackend::new(&mut inner);
+	vec.get(&mut outer, 5).unwrap();
+	let vec_hash = vec.deconstruct(&mut outer).unwrap();
+	let outer_proofs: Proofs<VecValue> = outer.into();
+
+	let vec = OwnedList::reconstruct(vec_hash.clone(), &mut inner, None).unwrap();
+	vec.get(&mut inner, 7).unwrap();
+	let inner_proofs: Proofs<VecValue> = inner.into();
+
+	assert!(outer_proofs.len() < inner_proofs.len());
+	assert!(outer_proofs.iter().all(|(k, v)| inner_proofs.get(k) == Some(v)));
+
+	let mut proved = InMemory::default();
+	proved.populate(outer_proofs.into());
+	let proved_vec = OwnedList::reconstruct(vec_hash.clone(), &mut proved, None).unwrap();
+	assert_eq!(proved_vec.get(&mut proved, 5).unwrap(), 5usize.into());
+
+	let mut proved = InMemory::default();
+	proved.populate(inner_proofs.into());
+	let proved_vec = OwnedList::reconstruct(vec_hash, &mut proved, None).unwrap();
+	assert_eq!(proved_vec.get(&mut proved, 5).unwrap(), 5usize.into());
+	assert_eq!(proved_vec.get(&mut proved, 7).unwrap(), 7usize.into());
+}
+
+#[test]
+fn query_prover_minimal() {
+	let mut db = InMemory::default();
+	let mut raw = OwnedRaw::default();
+	for i in 16..32 {
+		raw.set(&mut db, Index::from_one(i).unwrap(), i.into()).unwrap();
+	}
+
+	let indices = [16, 21, 29].iter().map(|i| Index::from_one(*i).unwrap()).collect::<Vec<_>>();
+	let prover = QueryProver::new(indices);
+	assert_eq!(prover.helper_indices(),
+			   [28, 20, 17, 15, 11, 9, 6].iter().map(|i| Index::from_one(*i).unwrap()).collect::<Vec<_>>());
+
+	let proof = prover.prove(&raw, &mut db).unwrap();
+	assert_eq!(proof.leaves.iter().map(|(_, v)| v.clone().into()).collect::<Vec<usize>>(), vec![16, 21, 29]);
+	assert_eq!(proof.helpers.len(), 7);
+	assert!(proof.verify::<bm::InheritedDigestConstruct<Sha256, VecValue>>(&raw.root()));
+
+	let mut tampered = proof.clone();
+	tampered.leaves[1].1 = 22usize.into();
+	assert!(!tampered.verify::<bm::InheritedDigestConstruct<Sha256, VecValue>>(&raw.root()));
+
+	let mut incomplete = proof.clone();
+	incomplete.helpers.pop();
+	assert!(!incomplete.verify::<bm::InheritedDigestConstruct<Sha256, VecValue>>(&raw.root()));
+}
+
+#[test]
+fn prove_into_compact() {
+	let mut db = InMemory::default();
+	let mut vec = OwnedList::create(&mut db, None).unwrap();
+	for i in 0..100 {
+		vec.push(&mut db, i.into()).unwrap();
+	}
+	let root = vec.root();
+
+	let mut proving = ProvingBackend::new(&mut db);
+	vec.get(&mut proving, 5).unwrap();
+	vec.get(&mut proving, 70).unwrap();
+	let manual = Proofs::from(proving).into_compact(root.clone());
+
+	let mut proving = ProvingBackend::new(&mut db);
+	vec.get(&mut proving, 5).unwrap();
+	vec.get(&mut proving, 70).unwrap();
+	let compact = proving.prove_into_compact(root.clone());
+
+	assert_eq!(compact, manual);
+	assert_eq!(compact.root::<bm::InheritedDigestConstruct<Sha256, VecValue>>(), root);
+}
+
+#[test]
+fn compact_value_hash() {
+	let single = CompactValue::Single(VecValue::from(1));
+	let combined = CompactValue::Combined(Box::new((
+		CompactValue::Single(VecValue::from(1)),
+		CompactValue::Single(VecValue::from(2)),
+	)));
+
+	let mut set = HashSet::new();
+	assert!(set.insert(single.clone()));
+	assert!(set.insert(combined.clone()));
+	assert!(!set.insert(single.clone()));
+	assert_eq!(set.len(), 2);
+	assert!(set.contains(&single));
+	assert!(set.contains(&combined));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn compact_value_flat_serde() {
+	#[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+	struct Flat(#[serde(with = "bm::compact_flat")] CompactValue<u64>);
+
+	let compact = CompactValue::from_plain(1u64, vec![2, 3], Index::root().right().left()).unwrap();
+	let json = serde_json::to_string(&Flat(compact.clone())).unwrap();
+	assert_eq!(json, r#"{"leaf":1,"siblings":[{"direction":"right","value":2},{"direction":"left","value":3}]}"#);
+	assert_eq!(serde_json::from_str::<Flat>(&json).unwrap(), Flat(compact));
+
+	let right = Flat(CompactValue::from_plain(1u64, vec![2, 3], Index::root().left().right()).unwrap());
+	let json = serde_json::to_string(&right).unwrap();
+	assert_eq!(serde_json::from_str::<Flat>(&json).unwrap(), right);
+
+	let single = Flat(CompactValue::Single(1u64));
+	let json = serde_json::to_string(&single).unwrap();
+	assert_eq!(json, r#"{"leaf":1,"siblings":[]}"#);
+	assert_eq!(serde_json::from_str::<Flat>(&json).unwrap(), single);
+
+	let multi = CompactValue::Combined(Box::new((
+		CompactValue::Combined(Box::new((CompactValue::Single(1u64), CompactValue::Single(2)))),
+		CompactValue::Combined(Box::new((CompactValue::Single(3), CompactValue::Single(4)))),
+	)));
+	assert!(serde_json::to_string(&Flat(multi)).is_err());
+}
+
+mod fixed {
+	use bm::{verify_proof, verify_proof_fixed, OwnedVector, Index, Tree, ReadBackend};
+	use core::convert::TryInto;
+	use super::{InMemory, VecValue};
+
+	type Construct = bm::InheritedDigestConstruct<sha2::Sha256, VecValue>;
+
+	#[test]
+	fn fixed_depth_proof() {
+		let mut db = InMemory::default();
+		let vec = OwnedVector::<Construct>::create_with(&mut db, 16, None, VecValue::from).unwrap();
+		let index = Index::from_depth(5, 4);
+
+		let mut current = vec.root();
+		let mut siblings = Vec::new();
+		for depth in (0..4).rev() {
+			let (left, right) = db.get(&current).unwrap().unwrap();
+			if (5 >> depth) & 1 == 0 {
+				siblings.push(right);
+				current = left;
+			} else {
+				siblings.push(left);
+				current = right;
+			}
+		}
+		assert_eq!(current, VecValue::from(5));
+		siblings.reverse();
+		assert!(verify_proof::<Construct, _>(&vec.root(), VecValue::from(5), siblings.clone(), index));
+
+		let fixed: [VecValue; 4] = siblings.try_into().unwrap();
+		assert!(verify_proof_fixed::<Construct, 4>(&vec.root(), VecValue::from(5), index, fixed.clone()));
+		assert!(!verify_proof_fixed::<Construct, 4>(&vec.root(), VecValue::from(6), index, fixed.clone()));
+		assert!(!verify_proof_fixed::<Construct, 4>(&vec.root(), VecValue::from(5), Index::from_depth(4, 4), fixed.clone()));
+		assert!(!verify_proof_fixed::<Construct, 4>(&vec.root(), VecValue::from(5), Index::from_depth(5, 5), fixed.clone()));
+		assert!(!verify_proof_fixed::<Construct, 3>(&vec.root(), VecValue::from(5), Index::from_depth(5, 4),
+													 [fixed[0].clone(), fixed[1].clone(), fixed[2].clone()]));
+	}
+}